@@ -0,0 +1,12 @@
+ALTER TABLE Contacts ADD COLUMN phone TEXT NOT NULL DEFAULT '', ADD COLUMN email TEXT NOT NULL DEFAULT '';
+
+UPDATE Contacts SET phone = p.number FROM contact_phones p WHERE p.contact_id = Contacts.id AND p.is_primary;
+UPDATE Contacts SET email = e.address FROM contact_emails e WHERE e.contact_id = Contacts.id AND e.is_primary;
+-- The email used to be required and unique, so contacts without one get a unique placeholder.
+UPDATE Contacts SET email = id || '@invalid' WHERE email = '';
+
+ALTER TABLE Contacts ALTER COLUMN phone DROP DEFAULT, ALTER COLUMN email DROP DEFAULT;
+ALTER TABLE Contacts ADD CONSTRAINT contacts_email_key UNIQUE (email);
+
+DROP TABLE contact_emails;
+DROP TABLE contact_phones;
@@ -0,0 +1,28 @@
+CREATE TABLE contact_phones (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    label TEXT NOT NULL,
+    number TEXT NOT NULL,
+    is_primary BOOLEAN NOT NULL DEFAULT FALSE
+);
+CREATE INDEX contact_phones_contact_id ON contact_phones (contact_id);
+CREATE UNIQUE INDEX contact_phones_one_primary ON contact_phones (contact_id) WHERE is_primary;
+
+CREATE TABLE contact_emails (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    label TEXT NOT NULL,
+    address TEXT NOT NULL UNIQUE,
+    is_primary BOOLEAN NOT NULL DEFAULT FALSE
+);
+CREATE INDEX contact_emails_contact_id ON contact_emails (contact_id);
+CREATE UNIQUE INDEX contact_emails_one_primary ON contact_emails (contact_id) WHERE is_primary;
+
+INSERT INTO contact_phones (contact_id, position, label, number, is_primary)
+    SELECT id, 1, 'other', phone, TRUE FROM Contacts WHERE phone <> '';
+INSERT INTO contact_emails (contact_id, position, label, address, is_primary)
+    SELECT id, 1, 'other', email, TRUE FROM Contacts WHERE email <> '';
+
+ALTER TABLE Contacts DROP COLUMN phone, DROP COLUMN email;
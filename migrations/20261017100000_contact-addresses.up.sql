@@ -0,0 +1,12 @@
+CREATE TABLE contact_addresses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    label TEXT NOT NULL,
+    street TEXT NOT NULL,
+    locality TEXT NOT NULL,
+    region TEXT NOT NULL,
+    postal_code TEXT NOT NULL,
+    country TEXT NOT NULL
+);
+CREATE INDEX contact_addresses_contact_id ON contact_addresses (contact_id);
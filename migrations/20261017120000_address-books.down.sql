@@ -0,0 +1,5 @@
+ALTER TABLE contact_emails DROP COLUMN book_id;
+ALTER TABLE contact_emails ADD CONSTRAINT contact_emails_address_key UNIQUE (address);
+ALTER TABLE Contacts DROP CONSTRAINT contacts_id_book_id_key;
+ALTER TABLE Contacts DROP COLUMN book_id;
+DROP TABLE address_books;
@@ -0,0 +1,20 @@
+CREATE TABLE address_books (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE
+);
+INSERT INTO address_books (name) VALUES ('Contacts');
+
+ALTER TABLE Contacts ADD COLUMN book_id UUID REFERENCES address_books (id) ON DELETE CASCADE;
+UPDATE Contacts SET book_id = (SELECT id FROM address_books);
+ALTER TABLE Contacts ALTER COLUMN book_id SET NOT NULL;
+CREATE INDEX contacts_book_id ON Contacts (book_id);
+
+-- Emails only have to be unique within an address book, so they carry the book of their contact.
+ALTER TABLE Contacts ADD CONSTRAINT contacts_id_book_id_key UNIQUE (id, book_id);
+ALTER TABLE contact_emails ADD COLUMN book_id UUID;
+UPDATE contact_emails e SET book_id = c.book_id FROM Contacts c WHERE c.id = e.contact_id;
+ALTER TABLE contact_emails
+    ALTER COLUMN book_id SET NOT NULL,
+    ADD FOREIGN KEY (contact_id, book_id) REFERENCES Contacts (id, book_id) ON DELETE CASCADE,
+    DROP CONSTRAINT contact_emails_address_key,
+    ADD CONSTRAINT contact_emails_book_id_address_key UNIQUE (book_id, address);
@@ -0,0 +1 @@
+ALTER TABLE Contacts ADD COLUMN notes TEXT NOT NULL DEFAULT '';
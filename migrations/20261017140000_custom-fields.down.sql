@@ -0,0 +1,3 @@
+ALTER TABLE Contacts DROP COLUMN custom;
+DROP TABLE custom_fields;
+DROP TYPE custom_field_kind;
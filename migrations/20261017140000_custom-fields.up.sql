@@ -0,0 +1,13 @@
+CREATE TYPE custom_field_kind AS ENUM ('text', 'number', 'date', 'boolean', 'select');
+
+CREATE TABLE custom_fields (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL REFERENCES address_books (id) ON DELETE CASCADE,
+    position SERIAL,
+    name TEXT NOT NULL,
+    kind custom_field_kind NOT NULL,
+    options TEXT[] NOT NULL DEFAULT '{}',
+    UNIQUE (book_id, name)
+);
+
+ALTER TABLE Contacts ADD COLUMN custom JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1 @@
+ALTER TABLE Contacts DROP COLUMN has_photo;
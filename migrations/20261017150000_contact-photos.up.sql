@@ -0,0 +1 @@
+ALTER TABLE Contacts ADD COLUMN has_photo BOOLEAN NOT NULL DEFAULT false;
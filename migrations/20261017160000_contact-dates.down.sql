@@ -0,0 +1,2 @@
+ALTER TABLE Contacts DROP COLUMN anniversary;
+ALTER TABLE Contacts DROP COLUMN birthday;
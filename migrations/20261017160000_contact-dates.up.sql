@@ -0,0 +1,3 @@
+-- Dates are stored as 'YYYY-MM-DD', or as '--MM-DD' if the year is unknown.
+ALTER TABLE Contacts ADD COLUMN birthday TEXT;
+ALTER TABLE Contacts ADD COLUMN anniversary TEXT;
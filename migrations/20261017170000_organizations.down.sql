@@ -0,0 +1,5 @@
+ALTER TABLE Contacts DROP COLUMN department;
+ALTER TABLE Contacts DROP COLUMN job_title;
+ALTER TABLE Contacts DROP COLUMN organization_id;
+
+DROP TABLE organizations;
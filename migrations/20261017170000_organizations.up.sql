@@ -0,0 +1,13 @@
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL REFERENCES address_books (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    website TEXT NOT NULL DEFAULT '',
+    notes TEXT NOT NULL DEFAULT '',
+    UNIQUE (book_id, name)
+);
+
+ALTER TABLE Contacts ADD COLUMN organization_id UUID REFERENCES organizations (id) ON DELETE SET NULL;
+ALTER TABLE Contacts ADD COLUMN job_title TEXT NOT NULL DEFAULT '';
+ALTER TABLE Contacts ADD COLUMN department TEXT NOT NULL DEFAULT '';
+CREATE INDEX contacts_organization_id ON Contacts (organization_id);
@@ -0,0 +1,2 @@
+DROP TABLE contact_relations;
+DROP TYPE relation_kind;
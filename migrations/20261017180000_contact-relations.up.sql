@@ -0,0 +1,16 @@
+CREATE TYPE relation_kind AS ENUM ('spouse', 'assistant', 'reports_to', 'referred_by');
+
+-- Read as "contact_id is <kind> of related_id", e.g. "contact_id reports to related_id".
+CREATE TABLE contact_relations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    related_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    kind relation_kind NOT NULL,
+    UNIQUE (contact_id, related_id, kind),
+    CHECK (contact_id <> related_id)
+);
+CREATE INDEX contact_relations_related_id ON contact_relations (related_id);
+-- Spouses are each other's, so the relation may only be recorded in one of the two directions.
+CREATE UNIQUE INDEX contact_relations_symmetric
+    ON contact_relations (least(contact_id, related_id), greatest(contact_id, related_id), kind)
+    WHERE kind = 'spouse';
@@ -0,0 +1,2 @@
+DELETE FROM Contacts WHERE deleted_at IS NOT NULL;
+ALTER TABLE Contacts DROP COLUMN deleted_at;
@@ -0,0 +1,2 @@
+ALTER TABLE Contacts ADD COLUMN deleted_at TIMESTAMPTZ;
+CREATE INDEX contacts_deleted_at ON Contacts (deleted_at) WHERE deleted_at IS NOT NULL;
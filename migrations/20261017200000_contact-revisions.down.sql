@@ -0,0 +1,2 @@
+DROP TABLE contact_revisions;
+DROP TYPE revision_action;
@@ -0,0 +1,10 @@
+CREATE TYPE revision_action AS ENUM ('create', 'update', 'delete', 'restore');
+
+CREATE TABLE contact_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+    action revision_action NOT NULL,
+    snapshot JSONB NOT NULL
+);
+CREATE INDEX contact_revisions_contact_id ON contact_revisions (contact_id, created_at);
@@ -0,0 +1,2 @@
+ALTER TABLE contact_revisions DROP COLUMN version;
+ALTER TABLE Contacts DROP COLUMN version;
@@ -0,0 +1,5 @@
+ALTER TABLE Contacts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- The version of the contact each revision is the state of, so that concurrent edits can be
+-- compared against the version they are based on. The revisions so far all belong to version 1.
+ALTER TABLE contact_revisions ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1 @@
+ALTER TABLE Contacts DROP COLUMN starred;
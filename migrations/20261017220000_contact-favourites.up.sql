@@ -0,0 +1 @@
+ALTER TABLE Contacts ADD COLUMN starred BOOLEAN NOT NULL DEFAULT false;
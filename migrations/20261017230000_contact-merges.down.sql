@@ -0,0 +1,6 @@
+DROP TABLE contact_merges;
+DELETE FROM contact_revisions WHERE action = 'merge';
+ALTER TYPE revision_action RENAME TO revision_action_old;
+CREATE TYPE revision_action AS ENUM ('create', 'update', 'delete', 'restore');
+ALTER TABLE contact_revisions ALTER COLUMN action TYPE revision_action USING action::TEXT::revision_action;
+DROP TYPE revision_action_old;
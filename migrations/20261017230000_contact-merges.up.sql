@@ -0,0 +1,11 @@
+ALTER TYPE revision_action ADD VALUE 'merge';
+
+-- What the contacts merged into contact_id looked like, for auditing.
+CREATE TABLE contact_merges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+    merged_ids UUID[] NOT NULL,
+    merged JSONB NOT NULL
+);
+CREATE INDEX contact_merges_contact_id ON contact_merges (contact_id);
@@ -0,0 +1,2 @@
+DROP TABLE interactions;
+DROP TYPE interaction_kind;
@@ -0,0 +1,11 @@
+CREATE TYPE interaction_kind AS ENUM ('call', 'meeting', 'email', 'note');
+
+CREATE TABLE interactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id UUID NOT NULL REFERENCES Contacts (id) ON DELETE CASCADE,
+    happened_on DATE NOT NULL,
+    kind interaction_kind NOT NULL,
+    text TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+CREATE INDEX interactions_contact_id ON interactions (contact_id, happened_on);
@@ -0,0 +1,13 @@
+ALTER TABLE address_books DROP COLUMN name_format;
+
+DROP TYPE name_format;
+
+ALTER TABLE Contacts
+    DROP COLUMN sort_last,
+    DROP COLUMN sort_first,
+    DROP COLUMN phonetic_last,
+    DROP COLUMN phonetic_first,
+    DROP COLUMN nickname,
+    DROP COLUMN suffix,
+    DROP COLUMN middle,
+    DROP COLUMN prefix;
@@ -0,0 +1,13 @@
+ALTER TABLE Contacts
+    ADD COLUMN prefix TEXT NOT NULL DEFAULT '',
+    ADD COLUMN middle TEXT NOT NULL DEFAULT '',
+    ADD COLUMN suffix TEXT NOT NULL DEFAULT '',
+    ADD COLUMN nickname TEXT NOT NULL DEFAULT '',
+    ADD COLUMN phonetic_first TEXT NOT NULL DEFAULT '',
+    ADD COLUMN phonetic_last TEXT NOT NULL DEFAULT '',
+    ADD COLUMN sort_first TEXT GENERATED ALWAYS AS (lower(COALESCE(NULLIF(phonetic_first, ''), first))) STORED,
+    ADD COLUMN sort_last TEXT GENERATED ALWAYS AS (lower(COALESCE(NULLIF(phonetic_last, ''), last))) STORED;
+
+CREATE TYPE name_format AS ENUM ('first_last', 'last_first');
+
+ALTER TABLE address_books ADD COLUMN name_format name_format NOT NULL DEFAULT 'first_last';
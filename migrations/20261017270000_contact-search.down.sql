@@ -0,0 +1,3 @@
+DROP INDEX contacts_search;
+
+ALTER TABLE Contacts DROP COLUMN search, DROP COLUMN search_details;
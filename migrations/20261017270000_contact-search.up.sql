@@ -0,0 +1,27 @@
+-- The phones, emails, addresses, tags and company of a contact live in other tables, which a
+-- generated column can't read, so they are copied into search_details whenever they change.
+ALTER TABLE Contacts ADD COLUMN search_details TEXT NOT NULL DEFAULT '';
+
+UPDATE Contacts c SET search_details = CONCAT_WS(' ',
+    (SELECT o.name FROM organizations o WHERE o.id = c.organization_id),
+    (SELECT string_agg(p.number || ' ' || regexp_replace(p.number, '\D', '', 'g'), ' ')
+        FROM contact_phones p WHERE p.contact_id = c.id),
+    (SELECT string_agg(
+        e.address || ' ' || split_part(e.address, '@', 2) || ' ' || translate(e.address, '@.+', '   '), ' ')
+        FROM contact_emails e WHERE e.contact_id = c.id),
+    (SELECT string_agg(CONCAT_WS(' ', a.street, a.postal_code, a.locality, a.region, a.country), ' ')
+        FROM contact_addresses a WHERE a.contact_id = c.id),
+    (SELECT string_agg(t.name, ' ')
+        FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id WHERE ct.contact_id = c.id)
+);
+
+ALTER TABLE Contacts ADD COLUMN search TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple',
+        first || ' ' || last || ' ' || middle || ' ' || nickname || ' ' || phonetic_first || ' ' || phonetic_last), 'A')
+    || setweight(to_tsvector('simple', prefix || ' ' || suffix || ' ' || job_title || ' ' || department), 'B')
+    || setweight(to_tsvector('simple', search_details), 'C')
+    || setweight(to_tsvector('simple', notes), 'D')
+    || setweight(jsonb_to_tsvector('simple', custom, '["string", "numeric"]'), 'D')
+) STORED;
+
+CREATE INDEX contacts_search ON Contacts USING GIN (search);
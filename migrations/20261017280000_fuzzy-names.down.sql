@@ -0,0 +1,3 @@
+DROP INDEX contacts_name_trgm;
+
+DROP EXTENSION IF EXISTS pg_trgm;
@@ -0,0 +1,3 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX contacts_name_trgm ON Contacts USING GIN ((first || ' ' || last) gin_trgm_ops);
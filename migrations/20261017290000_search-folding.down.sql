@@ -0,0 +1,19 @@
+DROP INDEX contact_emails_address_folded;
+DROP INDEX contact_emails_address;
+ALTER TABLE contact_emails ADD CONSTRAINT contact_emails_book_id_address_key UNIQUE (book_id, address);
+
+DROP INDEX contacts_search;
+ALTER TABLE Contacts DROP COLUMN search;
+ALTER TABLE Contacts ADD COLUMN search TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple',
+        first || ' ' || last || ' ' || middle || ' ' || nickname || ' ' || phonetic_first || ' ' || phonetic_last), 'A')
+    || setweight(to_tsvector('simple', prefix || ' ' || suffix || ' ' || job_title || ' ' || department), 'B')
+    || setweight(to_tsvector('simple', search_details), 'C')
+    || setweight(to_tsvector('simple', notes), 'D')
+    || setweight(jsonb_to_tsvector('simple', custom, '["string", "numeric"]'), 'D')
+) STORED;
+CREATE INDEX contacts_search ON Contacts USING GIN (search);
+
+DROP FUNCTION search_fold_values;
+DROP FUNCTION search_fold;
+DROP EXTENSION IF EXISTS unaccent;
@@ -0,0 +1,37 @@
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+-- Folds text for searching, so that "Mühmel", "Muhmel" and "Muehmel" or "Ærø" and "Aeroe" are
+-- the same: lowercase, without accents and with the German and Nordic transliterations ae, oe, ue,
+-- aa and ss reduced to a single letter. unaccent itself isn't immutable as its dictionary could
+-- change, so it is called with the dictionary spelled out to be usable in indexes.
+CREATE FUNCTION search_fold(value TEXT) RETURNS TEXT
+    LANGUAGE SQL IMMUTABLE STRICT PARALLEL SAFE
+    RETURN replace(replace(replace(replace(replace(
+        lower(public.unaccent('public.unaccent'::regdictionary, value)),
+        'ae', 'a'), 'oe', 'o'), 'ue', 'u'), 'aa', 'a'), 'ss', 's');
+
+-- The folded string and number values of custom fields, also the ones nested in lists. They are
+-- folded one by one, as folding the JSON itself could break it, e.g. by turning “ into ".
+CREATE FUNCTION search_fold_values(value JSONB) RETURNS TEXT
+    LANGUAGE SQL IMMUTABLE STRICT PARALLEL SAFE
+    RETURN (
+        SELECT string_agg(search_fold(v #>> '{}'), ' ')
+        FROM jsonb_path_query(value, 'strict $.**') v
+        WHERE jsonb_typeof(v) IN ('string', 'number')
+    );
+
+DROP INDEX contacts_search;
+ALTER TABLE Contacts DROP COLUMN search;
+ALTER TABLE Contacts ADD COLUMN search TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', search_fold(
+        first || ' ' || last || ' ' || middle || ' ' || nickname || ' ' || phonetic_first || ' ' || phonetic_last)), 'A')
+    || setweight(to_tsvector('simple', search_fold(prefix || ' ' || suffix || ' ' || job_title || ' ' || department)), 'B')
+    || setweight(to_tsvector('simple', search_fold(search_details)), 'C')
+    || setweight(to_tsvector('simple', search_fold(notes)), 'D')
+    || setweight(to_tsvector('simple', COALESCE(search_fold_values(custom), '')), 'D')
+) STORED;
+CREATE INDEX contacts_search ON Contacts USING GIN (search);
+
+ALTER TABLE contact_emails DROP CONSTRAINT contact_emails_book_id_address_key;
+CREATE UNIQUE INDEX contact_emails_address ON contact_emails (book_id, lower(address));
+CREATE INDEX contact_emails_address_folded ON contact_emails (book_id, search_fold(address));
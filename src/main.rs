@@ -1,5 +1,6 @@
 use axum::{
-    extract::FromRef,
+    extract::{DefaultBodyLimit, FromRef},
+    handler::Handler,
     http::StatusCode,
     response::{IntoResponse, Response},
 };
@@ -14,8 +15,17 @@ mod pages;
 #[derive(Clone, FromRef)]
 struct AppState {
     db: PgPool,
+    books: model::AddressBooks,
     contacts: model::Contacts,
-    archiver: model::Archiver,
+    custom_fields: model::CustomFields,
+    organizations: model::Organizations,
+    photos: model::Photos,
+    relations: model::Relations,
+    interactions: model::Interactions,
+    reminders: model::Reminders,
+    revisions: model::Revisions,
+    purger: model::Purger,
+    archivers: model::Archivers,
     flash_config: axum_flash::Config,
 }
 
@@ -30,15 +40,43 @@ async fn main() -> anyhow::Result<()> {
     let db = sqlx::PgPool::connect_with(db_options).await?;
     sqlx::migrate!().run(&db).await?;
 
+    let books = model::AddressBooks::new(db.clone());
     let contacts = model::Contacts::new(db.clone());
-    let archiver = model::Archiver::new(contacts.clone()).await?;
+    let custom_fields = contacts.custom_fields();
+    let organizations = model::Organizations::new(db.clone());
+    let photos_dir = std::env::var("PHOTOS_DIR").unwrap_or_else(|_| String::from("run/photos"));
+    let photos = model::Photos::new(db.clone(), photos_dir.into());
+    let max_photo_megabytes: usize = match std::env::var("MAX_PHOTO_MB") {
+        Ok(megabytes) => megabytes.parse()?,
+        Err(_) => 25,
+    };
+    let relations = model::Relations::new(db.clone());
+    let revisions = model::Revisions::new(db.clone());
+    let interactions = model::Interactions::new(db.clone());
+    let reminders = model::Reminders::new(db.clone());
+    let retention_days = match std::env::var("TRASH_RETENTION_DAYS") {
+        Ok(days) => days.parse()?,
+        Err(_) => 30,
+    };
+    let purger = model::Purger::new(contacts.clone(), photos.clone(), retention_days);
+    purger.spawn();
+    let archivers = model::Archivers::new(books.clone(), contacts.clone());
 
     let flash_config = axum_flash::Config::new(axum_flash::Key::generate());
 
     let app_state = AppState {
         db,
+        books,
         contacts,
-        archiver,
+        custom_fields,
+        organizations,
+        photos,
+        relations,
+        interactions,
+        reminders,
+        revisions,
+        purger,
+        archivers,
         flash_config,
     };
 
@@ -48,24 +86,61 @@ async fn main() -> anyhow::Result<()> {
         listener,
         axum::Router::new()
             .typed_get(pages::get)
+            .typed_get(pages::books::get)
+            .typed_post(pages::books::post)
+            .typed_get(pages::books::fields::get)
+            .typed_post(pages::books::fields::post)
+            .typed_delete(pages::books::fields::item::delete)
+            .typed_post(pages::books::name_format::post)
+            .typed_get(pages::books::organizations::get)
+            .typed_post(pages::books::organizations::post)
+            .typed_get(pages::books::organizations::names::get)
+            .typed_get(pages::books::organizations::item::get)
+            .typed_post(pages::books::organizations::item::put)
+            .typed_delete(pages::books::organizations::item::delete)
+            .typed_get(pages::reminders::get)
+            .typed_get(pages::reminders::overdue::get)
             .typed_get(pages::contacts::get)
             .typed_post(pages::contacts::post)
             .typed_delete(pages::contacts::delete)
             .typed_get(pages::contacts::archive::get)
             .typed_post(pages::contacts::archive::post)
             .typed_delete(pages::contacts::archive::delete)
+            .typed_get(pages::contacts::archive::file::get)
             .typed_get(pages::contacts::count::get)
+            .typed_get(pages::contacts::duplicates::get)
+            .typed_get(pages::contacts::merge::get)
+            .typed_post(pages::contacts::merge::post)
             .typed_get(pages::contacts::new::get)
+            .typed_get(pages::contacts::tags::get)
+            .typed_get(pages::contacts::upcoming::get)
+            .typed_get(pages::contacts::trash::get)
+            .typed_post(pages::contacts::trash::item::post)
+            .typed_delete(pages::contacts::trash::item::delete)
             .typed_get(pages::contacts::item::get)
             .typed_get(pages::contacts::item::edit::get)
             .typed_post(pages::contacts::item::put)
             .typed_get(pages::contacts::item::email::get)
+            .typed_get(pages::contacts::item::history::get)
+            .typed_post(pages::contacts::item::interactions::post)
+            .typed_delete(pages::contacts::item::interactions::item::delete)
+            .typed_post(pages::contacts::item::reminders::post)
+            .typed_post(pages::contacts::item::reminders::item::post)
+            .typed_delete(pages::contacts::item::reminders::item::delete)
+            .typed_post(pages::contacts::item::history::item::post)
+            .typed_post(
+                pages::contacts::item::photo::post
+                    .layer(DefaultBodyLimit::max(max_photo_megabytes * 1024 * 1024)),
+            )
+            .typed_delete(pages::contacts::item::photo::delete)
+            .typed_get(pages::contacts::item::photo::file::get)
+            .typed_post(pages::contacts::item::relations::post)
+            .typed_get(pages::contacts::item::relations::candidates::get)
+            .typed_delete(pages::contacts::item::relations::item::delete)
+            .typed_post(pages::contacts::item::star::post)
+            .typed_delete(pages::contacts::item::star::delete)
             .typed_delete(pages::contacts::item::delete)
             .nest_service("/assets", tower_http::services::ServeDir::new("assets"))
-            .route_service(
-                &pages::contacts::archive::file::Path.to_string(),
-                tower_http::services::ServeFile::new("run/export.csv"),
-            )
             .typed_get(assets::get_style)
             .route_service(
                 &assets::StyleSource.to_string(),
@@ -1,9 +1,35 @@
-pub use archiver::{Archiver, ArchiverStatus};
-pub use contacts::{Contact, ContactCandidate, ContactId, Contacts};
+pub use archiver::{ArchiverStatus, Archivers};
+pub use books::{AddressBook, AddressBooks, BookId, NameFormat};
+pub use contacts::{
+    Address, Change, Contact, ContactCandidate, ContactId, ContactOrder, ContactSort, Contacts,
+    Duplicate, Email, Occasion, PartialDate, Phone, SortDirection, Upcoming,
+};
+pub use fields::{
+    format_value, CustomField, CustomFields, CustomValues, FieldCandidate, FieldId, FieldKind,
+};
+pub use interactions::{
+    Interaction, InteractionCandidate, InteractionId, InteractionKind, Interactions,
+};
+pub use organizations::{Organization, OrganizationCandidate, OrganizationId, Organizations};
+pub use photos::{PhotoSize, Photos};
+pub use purger::Purger;
+pub use relations::{Relation, RelationCandidate, RelationId, RelationKind, Relations};
+pub use reminders::{Reminder, ReminderCandidate, ReminderId, Reminders};
+pub use revisions::{Revision, RevisionAction, RevisionId, Revisions};
+use std::collections::BTreeMap;
 use tokio::sync::{mpsc, oneshot};
 
 mod archiver;
+mod books;
 mod contacts;
+mod fields;
+mod interactions;
+mod organizations;
+mod photos;
+mod purger;
+mod relations;
+mod reminders;
+mod revisions;
 
 type Result<T, E = self::Error> = std::result::Result<T, E>;
 
@@ -11,6 +37,36 @@ type Result<T, E = self::Error> = std::result::Result<T, E>;
 pub enum Error {
     #[error("contact with this email already exists")]
     DuplicateEmail,
+    #[error("address book with this name already exists")]
+    DuplicateBookName,
+    #[error("custom field with this name already exists")]
+    DuplicateFieldName,
+    #[error("custom field needs a name")]
+    MissingFieldName,
+    #[error("select field needs at least one option")]
+    MissingFieldOptions,
+    #[error("organization with this name already exists")]
+    DuplicateOrganizationName,
+    #[error("some custom fields have invalid values")]
+    InvalidCustomFields(BTreeMap<String, String>),
+    #[error("contact has invalid values")]
+    InvalidContact(BTreeMap<String, String>),
+    #[error("search is invalid: {0}")]
+    InvalidSearch(String),
+    #[error("invalid {0:?} date")]
+    InvalidDate(Occasion),
+    #[error("contact was changed by someone else in the meantime")]
+    EditConflict,
+    #[error("interaction needs a date like 2024-12-31")]
+    InvalidInteraction,
+    #[error("reminder needs a text and a due date like 2024-12-31")]
+    InvalidReminder,
+    #[error("contacts can't be merged this way")]
+    InvalidMerge,
+    #[error("contacts can't be related this way")]
+    InvalidRelation,
+    #[error("file is not a supported image")]
+    InvalidImage,
     #[error("unknown database error")]
     Database(#[from] sqlx::Error),
     #[error("unknown io error")]
@@ -1,21 +1,54 @@
-use std::{future, pin::Pin, sync::Arc, task::Poll};
+use std::{collections::HashMap, future, path::PathBuf, pin::Pin, sync::Arc, task::Poll};
 
 use futures::{
     stream::{BoxStream, StreamExt},
     Stream,
 };
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, Mutex};
 use tracing::{info, instrument};
 
-use crate::model::{Contact, Contacts, Error, Result};
+use crate::model::{AddressBooks, BookId, Contact, Contacts, Error, Result};
 
 use self::writer::Writer;
 
 mod writer;
 
+/// Hands out the archiver of each address book, spawning it on first use.
+#[derive(Clone, Debug)]
+pub struct Archivers {
+    books: AddressBooks,
+    contacts: Contacts,
+    archivers: Arc<Mutex<HashMap<BookId, Archiver>>>,
+}
+
+impl Archivers {
+    pub fn new(books: AddressBooks, contacts: Contacts) -> Self {
+        Self {
+            books,
+            contacts,
+            archivers: Default::default(),
+        }
+    }
+
+    /// The archiver of `book`, or `None` if there is no such address book.
+    pub async fn get(&self, book: BookId) -> Result<Option<Archiver>> {
+        let mut archivers = self.archivers.lock().await;
+        if let Some(archiver) = archivers.get(&book) {
+            return Ok(Some(archiver.clone()));
+        }
+        if self.books.get_by_id(book).await?.is_none() {
+            return Ok(None);
+        }
+        let archiver = Archiver::new(self.contacts.clone(), book).await?;
+        archivers.insert(book, archiver.clone());
+        Ok(Some(archiver))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Archiver {
     commands: mpsc::Sender<Command>,
+    book: BookId,
 }
 
 #[derive(Clone)]
@@ -32,11 +65,16 @@ enum Command {
 }
 
 impl Archiver {
-    pub async fn new(contacts: Contacts) -> Result<Self> {
+    pub async fn new(contacts: Contacts, book: BookId) -> Result<Self> {
         let (commands, recv) = mpsc::channel(1);
-        let inner = Inner::new(recv, contacts).await?;
+        let inner = Inner::new(recv, contacts, book).await?;
         tokio::spawn(inner.work());
-        Ok(Self { commands })
+        Ok(Self { commands, book })
+    }
+
+    /// Where the finished archive of this address book is written to.
+    pub fn file(&self) -> PathBuf {
+        export_path(self.book)
     }
 
     pub async fn status(&self) -> Result<ArchiverStatus> {
@@ -81,6 +119,7 @@ impl Stream for StreamWrapper {
 struct Inner {
     commands: mpsc::Receiver<Command>,
     contacts: Contacts,
+    book: BookId,
     state: State,
 }
 
@@ -98,10 +137,15 @@ struct Running {
 }
 
 impl Inner {
-    async fn new(commands: mpsc::Receiver<Command>, contacts: Contacts) -> Result<Self> {
+    async fn new(
+        commands: mpsc::Receiver<Command>,
+        contacts: Contacts,
+        book: BookId,
+    ) -> Result<Self> {
         Ok(Inner {
             commands,
             contacts,
+            book,
             state: State::Waiting,
         })
     }
@@ -114,7 +158,7 @@ impl Inner {
                     biased;
                     command = self.commands.recv() => {
                         match command {
-                            Some(command) => self.state = State::Running(running).handle_command(&self.contacts, command).await,
+                            Some(command) => self.state = State::Running(running).handle_command(&self.contacts, self.book, command).await,
                             None => break,
                         }
                     }
@@ -127,7 +171,7 @@ impl Inner {
                 tokio::select! {
                     command = self.commands.recv() => {
                         match command {
-                            Some(command) => self.state = self.state.handle_command(&self.contacts, command).await,
+                            Some(command) => self.state = self.state.handle_command(&self.contacts, self.book, command).await,
                             None => break,
                         }
                     }
@@ -138,9 +182,9 @@ impl Inner {
 }
 
 impl State {
-    async fn handle_command(self, contacts: &Contacts, command: Command) -> State {
+    async fn handle_command(self, contacts: &Contacts, book: BookId, command: Command) -> State {
         let res = match (self, command) {
-            (State::Waiting, Command::Start) => Self::start(contacts).await,
+            (State::Waiting, Command::Start) => Self::start(contacts, book).await,
             (s @ State::Waiting, Command::Reset) => Ok(s),
             (s @ State::Waiting, Command::GetStatus(ret)) => ret
                 .send(ArchiverStatus::Waiting)
@@ -152,7 +196,7 @@ impl State {
                 .send(ArchiverStatus::Running(count as f32 / total as f32))
                 .map_err(|_| Error::CommandSendFailed)
                 .map(|_| s),
-            (State::Complete(_), Command::Start) => Self::start(contacts).await,
+            (State::Complete(_), Command::Start) => Self::start(contacts, book).await,
             (State::Complete(_), Command::Reset) => Ok(Self::Waiting),
             (State::Complete(err), Command::GetStatus(ret)) => ret
                 .send(ArchiverStatus::Complete(
@@ -167,12 +211,16 @@ impl State {
         }
     }
 
-    async fn start(contacts: &Contacts) -> Result<Self> {
+    async fn start(contacts: &Contacts, book: BookId) -> Result<Self> {
         Ok(Self::Running(Running {
-            total: contacts.count().await?,
-            stream: StreamWrapper::new(contacts.clone(), |c| Box::pin(c.get_all())),
+            total: contacts.count(book).await?,
+            stream: StreamWrapper::new(contacts.clone(), move |c| Box::pin(c.get_all(book))),
             count: 0,
-            writer: Writer::new().await?,
+            writer: Writer::new(
+                export_path(book),
+                contacts.custom_fields().get_all(book).await?,
+            )
+            .await?,
         }))
     }
 }
@@ -200,6 +248,10 @@ impl Running {
     }
 }
 
+fn export_path(book: BookId) -> PathBuf {
+    PathBuf::from(format!("run/export-{book}.csv"))
+}
+
 async fn yield_once() {
     let mut pending = true;
     future::poll_fn(|ctx| {
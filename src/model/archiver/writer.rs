@@ -1,15 +1,17 @@
 use std::{
+    borrow::Cow,
     fs::File,
     io::{BufWriter, Result, Write},
     mem,
+    path::PathBuf,
 };
 
 use tokio::sync::{mpsc, oneshot};
 
-use crate::model::Contact;
+use crate::model::{format_value, Contact, CustomField};
 
 enum Command {
-    Write(Contact),
+    Write(Box<Contact>),
     Finish(oneshot::Sender<Result<()>>),
 }
 
@@ -18,10 +20,11 @@ pub struct Writer {
 }
 
 impl Writer {
-    pub async fn new() -> Result<Writer> {
+    /// Starts writing to `path`, with one extra column per custom field after the built-in ones.
+    pub async fn new(path: PathBuf, fields: Vec<CustomField>) -> Result<Writer> {
         let (tx, rx) = mpsc::unbounded_channel();
 
-        let mut inner = tokio::task::spawn_blocking(|| Inner::new(rx))
+        let mut inner = tokio::task::spawn_blocking(|| Inner::new(rx, path, fields))
             .await
             .unwrap()?;
 
@@ -31,7 +34,9 @@ impl Writer {
     }
 
     pub fn write(&self, contact: Contact) {
-        self.commands.send(Command::Write(contact)).unwrap();
+        self.commands
+            .send(Command::Write(Box::new(contact)))
+            .unwrap();
     }
 
     pub async fn flush(&self) -> Result<()> {
@@ -45,14 +50,26 @@ struct Inner {
     commands: mpsc::UnboundedReceiver<Command>,
     res: Result<()>,
     file: BufWriter<File>,
+    fields: Vec<CustomField>,
 }
 
 impl Inner {
-    fn new(commands: mpsc::UnboundedReceiver<Command>) -> Result<Self> {
+    fn new(
+        commands: mpsc::UnboundedReceiver<Command>,
+        path: PathBuf,
+        fields: Vec<CustomField>,
+    ) -> Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "id,first,last,prefix,middle,suffix,nickname,phonetic_first,phonetic_last,phones,emails,addresses,tags,notes,birthday,anniversary,organization,job_title,department")?;
+        for field in &fields {
+            write!(file, ",{}", escape(&field.name))?;
+        }
+        writeln!(file)?;
         Ok(Self {
             commands,
             res: Ok(()),
-            file: BufWriter::new(File::create("run/export.csv")?),
+            file,
+            fields,
         })
     }
 
@@ -69,7 +86,7 @@ impl Inner {
 
     fn handle(&mut self, command: Command) -> Option<Result<()>> {
         match command {
-            Command::Write(contact) if self.res.is_ok() => Some(self.write(contact)),
+            Command::Write(contact) if self.res.is_ok() => Some(self.write(*contact)),
             Command::Write(_) => None,
             Command::Finish(result) => {
                 match mem::replace(&mut self.res, Ok(())) {
@@ -90,14 +107,88 @@ impl Inner {
             id,
             first,
             last,
-            phone,
-            email,
+            prefix,
+            middle,
+            suffix,
+            nickname,
+            phonetic_first,
+            phonetic_last,
+            phones,
+            emails,
+            addresses,
+            tags,
+            notes,
+            custom,
+            birthday,
+            anniversary,
+            organization,
+            job_title,
+            department,
+            ..
         }: Contact,
     ) -> Result<()> {
-        write!(self.file, "{id},{first},{last},{phone},{email}")
+        let phones = phones
+            .iter()
+            .map(|phone| format!("{}: {}", phone.label, phone.number))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let emails = emails
+            .iter()
+            .map(|email| format!("{}: {}", email.label, email.address))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let addresses = addresses
+            .iter()
+            .map(|address| format!("{}: {}", address.label, address.one_line()))
+            .collect::<Vec<_>>()
+            .join("; ");
+        write!(
+            self.file,
+            "{id},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            escape(&first),
+            escape(&last),
+            escape(&prefix),
+            escape(&middle),
+            escape(&suffix),
+            escape(&nickname),
+            escape(&phonetic_first),
+            escape(&phonetic_last),
+            escape(&phones),
+            escape(&emails),
+            escape(&addresses),
+            escape(&tags.join("; ")),
+            escape(&notes),
+            birthday.map(|date| date.to_string()).unwrap_or_default(),
+            anniversary.map(|date| date.to_string()).unwrap_or_default(),
+            escape(
+                organization
+                    .map(|org| org.name)
+                    .as_deref()
+                    .unwrap_or_default()
+            ),
+            escape(&job_title),
+            escape(&department),
+        )?;
+        for field in &self.fields {
+            let value = custom
+                .get(&field.id.to_string())
+                .map(format_value)
+                .unwrap_or_default();
+            write!(self.file, ",{}", escape(&value))?;
+        }
+        writeln!(self.file)
     }
 
     fn finish(&mut self) -> Result<()> {
         self.file.flush()
     }
 }
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
@@ -0,0 +1,143 @@
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::model::Result;
+
+mod id;
+pub use id::BookId;
+
+use super::Error;
+
+/// How the names of the contacts in an address book are shown and sorted.
+#[derive(sqlx::Type, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[sqlx(type_name = "name_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NameFormat {
+    /// "Ada Lovelace", sorted by first name.
+    #[default]
+    FirstLast,
+    /// "Lovelace, Ada", sorted by last name.
+    LastFirst,
+}
+
+impl NameFormat {
+    pub const ALL: [NameFormat; 2] = [NameFormat::FirstLast, NameFormat::LastFirst];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameFormat::FirstLast => "first_last",
+            NameFormat::LastFirst => "last_first",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NameFormat::FirstLast => "First Last",
+            NameFormat::LastFirst => "Last, First",
+        }
+    }
+
+    /// The full name made of the given parts in this format, leaving out empty ones.
+    pub fn full_name(
+        &self,
+        prefix: &str,
+        first: &str,
+        middle: &str,
+        last: &str,
+        suffix: &str,
+    ) -> String {
+        let join = |parts: &[&str]| {
+            parts
+                .iter()
+                .map(|part| part.trim())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        match self {
+            NameFormat::FirstLast => join(&[prefix, first, middle, last, suffix]),
+            NameFormat::LastFirst => {
+                let given = join(&[prefix, first, middle, suffix]);
+                match (last.trim(), given.as_str()) {
+                    ("", given) => given.to_owned(),
+                    (last, "") => last.to_owned(),
+                    (last, given) => format!("{last}, {given}"),
+                }
+            }
+        }
+    }
+}
+
+pub struct AddressBook {
+    pub id: BookId,
+    pub name: String,
+    pub count: i64,
+    pub name_format: NameFormat,
+}
+
+#[derive(Debug, Clone)]
+pub struct AddressBooks {
+    db: PgPool,
+}
+
+impl AddressBooks {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<AddressBook>> {
+        let books = sqlx::query_as!(
+            AddressBook,
+            r#"SELECT b.id as "id: BookId", b.name, COUNT(c.id) as "count!",
+                    b.name_format as "name_format: NameFormat"
+                FROM address_books b LEFT JOIN Contacts c ON c.book_id = b.id AND c.deleted_at IS NULL
+                GROUP BY b.id ORDER BY b.name"#
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(books)
+    }
+
+    pub async fn get_by_id(&self, id: BookId) -> Result<Option<AddressBook>> {
+        let book = sqlx::query_as!(
+            AddressBook,
+            r#"SELECT b.id as "id: BookId", b.name, COUNT(c.id) as "count!",
+                    b.name_format as "name_format: NameFormat"
+                FROM address_books b LEFT JOIN Contacts c ON c.book_id = b.id AND c.deleted_at IS NULL
+                WHERE b.id = $1 GROUP BY b.id"#,
+            id as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(book)
+    }
+
+    pub async fn create(&self, name: &str) -> Result<BookId> {
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO address_books (name) VALUES ($1) RETURNING id as "id: BookId""#,
+            name.trim()
+        )
+        .fetch_one(&self.db)
+        .await;
+        match result {
+            Ok(id) => Ok(id),
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                Err(Error::DuplicateBookName)
+            }
+            Err(err) => Err(err)?,
+        }
+    }
+
+    /// Changes how names are shown and sorted in the address book. Returns whether there was
+    /// such an address book.
+    pub async fn set_name_format(&self, id: BookId, name_format: NameFormat) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE address_books SET name_format = $1 WHERE id = $2",
+            name_format as NameFormat,
+            id as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,50 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Clone, Copy, Debug)]
+#[serde(try_from = "BookStringId", into = "BookStringId")]
+#[sqlx(transparent)]
+pub struct BookId(pub(super) Uuid);
+
+impl fmt::Display for BookId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Uuid> for BookId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for BookId {
+    type Err = <Uuid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::from_str(s)?))
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(transparent)]
+pub struct BookStringId {
+    id: String,
+}
+
+impl From<BookId> for BookStringId {
+    fn from(value: BookId) -> Self {
+        Self {
+            id: value.0.to_string(),
+        }
+    }
+}
+impl TryFrom<BookStringId> for BookId {
+    type Error = sqlx::types::uuid::Error;
+
+    fn try_from(value: BookStringId) -> Result<Self, Self::Error> {
+        Ok(Self(value.id.parse()?))
+    }
+}
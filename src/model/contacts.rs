@@ -1,20 +1,211 @@
-use futures::stream::{Stream, StreamExt};
-use serde::Deserialize;
-use sqlx::PgPool;
+use std::{cmp::Reverse, collections::HashMap};
 
-use crate::model::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    types::{Json, Uuid},
+    PgConnection, PgPool, Postgres, QueryBuilder,
+};
 
+use crate::model::{
+    fields, format_value, organizations, revisions, BookId, CustomField, CustomFields,
+    CustomValues, NameFormat, Organization, OrganizationId, Result, RevisionAction,
+};
+
+mod date;
+mod details;
+mod duplicates;
 mod id;
+mod query;
+mod validation;
+pub use date::PartialDate;
+pub use details::{Address, Email, Phone};
+pub use duplicates::Duplicate;
 pub use id::ContactId;
 
+pub(super) use self::details::refresh_search;
+use self::details::ContactRow;
+use self::duplicates::{Fingerprint, LIKELY_DUPLICATE};
+use self::query::SearchQuery;
 use super::Error;
 
+#[derive(Clone)]
 pub struct Contact {
     pub id: ContactId,
+    pub book: BookId,
     pub first: String,
     pub last: String,
-    pub phone: String,
-    pub email: String,
+    /// Honorific before the name, like "Dr.".
+    pub prefix: String,
+    pub middle: String,
+    /// Generational or academic suffix after the name, like "Jr." or "PhD".
+    pub suffix: String,
+    pub nickname: String,
+    /// How the first name is pronounced, e.g. in kana, which it is sorted by if given.
+    pub phonetic_first: String,
+    /// How the last name is pronounced, which it is sorted by if given.
+    pub phonetic_last: String,
+    pub phones: Vec<Phone>,
+    pub emails: Vec<Email>,
+    pub addresses: Vec<Address>,
+    pub tags: Vec<String>,
+    pub notes: String,
+    pub custom: CustomValues,
+    pub has_photo: bool,
+    pub birthday: Option<PartialDate>,
+    pub anniversary: Option<PartialDate>,
+    pub organization: Option<Organization>,
+    pub job_title: String,
+    pub department: String,
+    /// When the contact was moved to the trash.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Counts the updates, so that concurrent edits can be detected.
+    pub version: i32,
+    /// Favourites are listed before all other contacts.
+    pub starred: bool,
+    /// The date of the latest logged interaction.
+    pub last_interaction: Option<NaiveDate>,
+    /// How the address book of the contact shows names.
+    pub name_format: NameFormat,
+}
+
+impl Contact {
+    /// The full name with prefix, middle name and suffix, in the format of the address book.
+    pub fn name(&self) -> String {
+        self.name_format.full_name(
+            &self.prefix,
+            &self.first,
+            &self.middle,
+            &self.last,
+            &self.suffix,
+        )
+    }
+}
+
+/// What a page of contacts is ordered by, after the favourites.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactSort {
+    /// By the name in the format of the address book.
+    #[default]
+    Name,
+    Company,
+    /// By the primary phone number.
+    Phone,
+    /// By the primary email address.
+    Email,
+    LastInteraction,
+}
+
+impl ContactSort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContactSort::Name => "name",
+            ContactSort::Company => "company",
+            ContactSort::Phone => "phone",
+            ContactSort::Email => "email",
+            ContactSort::LastInteraction => "last_interaction",
+        }
+    }
+
+    /// The direction to sort in when first sorting by this: the most recent interaction first,
+    /// everything else alphabetically.
+    pub fn default_direction(&self) -> SortDirection {
+        match self {
+            ContactSort::LastInteraction => SortDirection::Desc,
+            _ => SortDirection::Asc,
+        }
+    }
+
+    /// Appends the sort keys of `Contacts` for this, contacts without a value last either way.
+    fn push_order_by(&self, direction: SortDirection, builder: &mut QueryBuilder<'_, Postgres>) {
+        let direction = direction.sql();
+        match self {
+            ContactSort::Name => {
+                builder.push(format!(
+                    " CASE WHEN b.last_first THEN sort_last ELSE sort_first END {direction},
+                        CASE WHEN b.last_first THEN sort_first ELSE sort_last END {direction},"
+                ));
+                return;
+            }
+            ContactSort::Company => builder.push(
+                " (SELECT lower(o.name) FROM organizations o WHERE o.id = Contacts.organization_id)",
+            ),
+            ContactSort::Phone => builder.push(
+                " (SELECT p.number FROM contact_phones p WHERE p.contact_id = Contacts.id AND p.is_primary)",
+            ),
+            ContactSort::Email => builder.push(
+                " (SELECT lower(e.address) FROM contact_emails e WHERE e.contact_id = Contacts.id AND e.is_primary)",
+            ),
+            ContactSort::LastInteraction => builder.push(" li.last_interaction"),
+        };
+        builder.push(format!(" {direction} NULLS LAST,"));
+    }
+}
+
+/// The order of a page of contacts, after the favourites.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct ContactOrder {
+    /// The column chosen to sort by. Without one, searches are ordered by relevance and
+    /// everything else by name.
+    pub sort: Option<ContactSort>,
+    pub direction: SortDirection,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    /// Alphabetically, or the oldest first.
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+
+    pub fn reversed(&self) -> Self {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
+
+    fn sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+/// How similar a name has to be to a search to be found by the fuzzy search, between 0 and 1.
+/// "Jonh Smyth" is 0.29 similar to "John Smith".
+const FUZZY_THRESHOLD: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Occasion {
+    Birthday,
+    Anniversary,
+}
+
+/// The next birthday or anniversary of a contact.
+pub struct Upcoming {
+    pub id: ContactId,
+    /// The full name in the format of the address book.
+    pub name: String,
+    pub occasion: Occasion,
+    pub date: NaiveDate,
+    /// Days from today until `date`, 0 if it is today.
+    pub days: i64,
+    /// The age or number of years married being completed on `date`, if the year is known.
+    pub years: Option<i32>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,138 +218,1408 @@ impl Contacts {
         Self { db }
     }
 
-    pub async fn count(&self) -> Result<u64> {
+    pub fn custom_fields(&self) -> CustomFields {
+        CustomFields::new(self.db.clone())
+    }
+
+    pub async fn count(&self, book: BookId) -> Result<u64> {
         // let (count, _) = tokio::join!(
         //     sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM Contacts"#).fetch_one(&self.db),
         //     tokio::time::sleep(std::time::Duration::from_secs(2))
         // );
-        let count = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM Contacts"#)
-            .fetch_one(&self.db)
-            .await;
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM Contacts WHERE book_id = $1 AND deleted_at IS NULL"#,
+            book as BookId
+        )
+        .fetch_one(&self.db)
+        .await;
         Ok(count? as u64)
     }
 
-    pub async fn get_by_id(&self, id: ContactId) -> Result<Option<Contact>> {
-        let contact = sqlx::query_as!(
-            Contact,
-            "SELECT id, first, last, phone, email FROM Contacts WHERE id = $1",
-            id as ContactId
+    pub async fn get_by_id(&self, book: BookId, id: ContactId) -> Result<Option<Contact>> {
+        let row = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts WHERE id = $1 AND book_id = $2 AND deleted_at IS NULL"#,
+            id as ContactId,
+            book as BookId
         )
         .fetch_optional(&self.db)
         .await?;
-        Ok(contact)
+        self.attach_one(row).await
     }
 
-    pub async fn get_by_email(&self, email: &str) -> Result<Option<Contact>> {
-        let contact = sqlx::query_as!(
-            Contact,
-            "SELECT id, first, last, phone, email FROM Contacts WHERE email = $1",
-            email
+    /// The contact in `book` with `email`, including contacts in the trash as their emails stay
+    /// taken until they are purged. Case, accents and transliterations like "ue" for "ü" are
+    /// ignored, but an exact match is preferred.
+    pub async fn get_by_email(&self, book: BookId, email: &str) -> Result<Option<Contact>> {
+        let row = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT c.id, c.book_id, c.first, c.last, c.prefix, c.middle, c.suffix, c.nickname,
+                    c.phonetic_first, c.phonetic_last, c.notes, c.custom as "custom: Json<CustomValues>",
+                    c.has_photo, c.birthday, c.anniversary,
+                    c.organization_id as "organization_id: OrganizationId", c.job_title, c.department,
+                    c.deleted_at, c.version, c.starred
+                FROM Contacts c
+                JOIN contact_emails e ON e.contact_id = c.id
+                WHERE e.book_id = $2 AND search_fold(e.address) = search_fold($1)
+                ORDER BY e.address = $1 DESC, lower(e.address) = lower($1) DESC
+                LIMIT 1"#,
+            email,
+            book as BookId
         )
         .fetch_optional(&self.db)
         .await?;
-        Ok(contact)
+        self.attach_one(row).await
     }
 
-    pub fn get_all(&self) -> impl Stream<Item = Result<Contact>> + '_ {
+    pub fn get_all(&self, book: BookId) -> impl Stream<Item = Result<Contact>> + '_ {
         sqlx::query_as!(
-            Contact,
-            "SELECT id, first, last, phone, email FROM Contacts"
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts WHERE book_id = $1 AND deleted_at IS NULL"#,
+            book as BookId
         )
         .fetch(&self.db)
-        .map(|res| Ok(res?))
+        .try_chunks(256)
+        .map_err(|err| err.1)
+        .and_then(|rows| details::attach(&self.db, rows))
+        .map_ok(|contacts| stream::iter(contacts).map(Ok::<_, sqlx::Error>))
+        .try_flatten()
+        .map_err(Error::from)
+    }
+
+    /// The contacts matching the search `search_term`, like
+    /// `ada "analytical engine" -tag:old email:@example.com has:phone`. Free words and phrases
+    /// match any of the text fields, ignoring accents and transliterations like "ue" for "ü", and
+    /// the last word also matches as a prefix, so that results show up while typing. Unless sorted
+    /// otherwise, the best matches come first.
+    pub async fn get_filtered_page(
+        &self,
+        book: BookId,
+        search_term: &str,
+        tag: Option<&str>,
+        favourites_only: bool,
+        order: ContactOrder,
+        page: u64,
+    ) -> Result<Vec<Contact>> {
+        let query: SearchQuery = search_term.parse().map_err(Error::InvalidSearch)?;
+        self.get_page_matching(book, &query, tag, favourites_only, order, page)
+            .await
     }
 
-    pub async fn get_filtered_page(&self, search_term: &str, page: u64) -> Result<Vec<Contact>> {
+    pub async fn get_page(
+        &self,
+        book: BookId,
+        tag: Option<&str>,
+        favourites_only: bool,
+        order: ContactOrder,
+        page: u64,
+    ) -> Result<Vec<Contact>> {
+        let query = SearchQuery::default();
+        self.get_page_matching(book, &query, tag, favourites_only, order, page)
+            .await
+    }
+
+    async fn get_page_matching(
+        &self,
+        book: BookId,
+        query: &SearchQuery,
+        tag: Option<&str>,
+        favourites_only: bool,
+        order: ContactOrder,
+        page: u64,
+    ) -> Result<Vec<Contact>> {
         let pagesize = 10;
         let offset = (page as i64 - 1) * pagesize;
-        let contacts = sqlx::query_as!(
-            Contact,
-            r"SELECT id, first, last, phone, email FROM Contacts 
-                    WHERE first ILIKE CONCAT('%', $1::TEXT, '%')
-                       OR last ILIKE CONCAT('%', $1::TEXT, '%')
-                    ORDER BY first, last, email ASC LIMIT $2 OFFSET $3
-                ",
-            search_term,
-            pagesize,
-            offset
-        )
-        .fetch_all(&self.db)
-        .await?;
-        Ok(contacts)
+        let mut builder = QueryBuilder::new(
+            "SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom, has_photo,
+                    birthday, anniversary, organization_id, job_title, department, deleted_at, version, starred
+                FROM Contacts
+                LEFT JOIN (
+                    SELECT contact_id, MAX(happened_on) AS last_interaction
+                        FROM interactions GROUP BY contact_id
+                ) li ON li.contact_id = Contacts.id
+                CROSS JOIN (
+                    SELECT name_format = 'last_first' AS last_first FROM address_books WHERE id = ",
+        );
+        builder.push_bind(book).push(") b");
+        if query.has_text() {
+            builder
+                .push(" CROSS JOIN (SELECT websearch_to_tsquery('simple', search_fold(")
+                .push_bind(query.text.clone())
+                .push(")) && COALESCE(to_tsquery('simple', search_fold(")
+                .push_bind(query.prefix.clone())
+                .push("::TEXT) || ':*'), ''::TSQUERY) AS query) q");
+        }
+        builder
+            .push(" WHERE book_id = ")
+            .push_bind(book)
+            .push(" AND deleted_at IS NULL");
+        if query.has_text() {
+            builder.push(" AND search @@ q.query");
+        }
+        query.push_filters(&mut builder);
+        if let Some(tag) = tag {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+                        WHERE ct.contact_id = Contacts.id AND t.name = ",
+                )
+                .push_bind(tag.to_owned())
+                .push(")");
+        }
+        if favourites_only {
+            builder.push(" AND starred");
+        }
+        builder.push(" ORDER BY starred DESC,");
+        match order.sort {
+            Some(sort) => sort.push_order_by(order.direction, &mut builder),
+            None if query.has_text() => {
+                builder.push(" ts_rank(search, q.query) DESC,");
+            }
+            None => {}
+        }
+        if order.sort != Some(ContactSort::Name) {
+            ContactSort::Name.push_order_by(SortDirection::Asc, &mut builder);
+        }
+        builder
+            .push(" id ASC LIMIT ")
+            .push_bind(pagesize)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let rows = builder
+            .build_query_as::<ContactRow>()
+            .fetch_all(&self.db)
+            .await?;
+        Ok(details::attach(&self.db, rows).await?)
     }
 
-    pub async fn get_page(&self, page: u64) -> Result<Vec<Contact>> {
+    /// The contacts whose name is similar to `search_term`, so that they are found despite typos.
+    /// Unless sorted otherwise, the most similar come first.
+    pub async fn get_fuzzy_page(
+        &self,
+        book: BookId,
+        search_term: &str,
+        tag: Option<&str>,
+        favourites_only: bool,
+        order: ContactOrder,
+        page: u64,
+    ) -> Result<Vec<Contact>> {
         let pagesize = 10;
         let offset = (page as i64 - 1) * pagesize;
-        let contacts = sqlx::query_as!(
-            Contact,
-            r"SELECT id, first, last, phone, email FROM Contacts 
-                ORDER BY first, last, email ASC LIMIT $1 OFFSET $2
-            ",
-            pagesize,
-            offset
+        let search_term = search_term.trim().to_owned();
+        let mut builder = QueryBuilder::new(
+            "SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom, has_photo,
+                    birthday, anniversary, organization_id, job_title, department, deleted_at, version, starred
+                FROM Contacts
+                LEFT JOIN (
+                    SELECT contact_id, MAX(happened_on) AS last_interaction
+                        FROM interactions GROUP BY contact_id
+                ) li ON li.contact_id = Contacts.id
+                CROSS JOIN (
+                    SELECT name_format = 'last_first' AS last_first FROM address_books WHERE id = ",
+        );
+        builder
+            .push_bind(book)
+            .push(") b WHERE book_id = ")
+            .push_bind(book)
+            .push(" AND deleted_at IS NULL AND (first || ' ' || last) % ")
+            .push_bind(search_term.clone());
+        if let Some(tag) = tag {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+                        WHERE ct.contact_id = Contacts.id AND t.name = ",
+                )
+                .push_bind(tag.to_owned())
+                .push(")");
+        }
+        if favourites_only {
+            builder.push(" AND starred");
+        }
+        builder.push(" ORDER BY starred DESC,");
+        match order.sort {
+            Some(sort) => sort.push_order_by(order.direction, &mut builder),
+            None => {
+                builder
+                    .push(" similarity(first || ' ' || last, ")
+                    .push_bind(search_term)
+                    .push(") DESC,");
+            }
+        }
+        if order.sort != Some(ContactSort::Name) {
+            ContactSort::Name.push_order_by(SortDirection::Asc, &mut builder);
+        }
+        builder
+            .push(" id ASC LIMIT ")
+            .push_bind(pagesize)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let mut tx = self.db.begin().await?;
+        set_fuzzy_threshold(&mut tx).await?;
+        let rows = builder
+            .build_query_as::<ContactRow>()
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(details::attach(&self.db, rows).await?)
+    }
+
+    /// Up to three distinct names in `book` that are similar to `search_term`, most similar
+    /// first, to suggest when searching for it finds nothing.
+    pub async fn get_name_suggestions(
+        &self,
+        book: BookId,
+        search_term: &str,
+    ) -> Result<Vec<String>> {
+        let mut tx = self.db.begin().await?;
+        set_fuzzy_threshold(&mut tx).await?;
+        let names = sqlx::query_scalar!(
+            r#"SELECT name as "name!" FROM (
+                    SELECT DISTINCT first || ' ' || last AS name FROM Contacts
+                        WHERE book_id = $2 AND deleted_at IS NULL AND (first || ' ' || last) % $1
+                ) names
+                ORDER BY similarity(name, $1) DESC, name
+                LIMIT 3"#,
+            search_term.trim(),
+            book as BookId
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(names)
+    }
+
+    /// The pairs of contacts in `book` that are likely the same person, most likely first.
+    /// Only pairs that share a phone number or email name or have similar names are scored, and
+    /// similar names are looked up in the trigram index, so that not every contact has to be
+    /// compared with every other.
+    pub async fn get_duplicates(&self, book: BookId) -> Result<Vec<Duplicate>> {
+        let mut tx = self.db.begin().await?;
+        set_fuzzy_threshold(&mut tx).await?;
+        let pairs = sqlx::query!(
+            r#"WITH candidates AS (
+                    SELECT id, first || ' ' || last AS name FROM Contacts
+                        WHERE book_id = $1 AND deleted_at IS NULL
+                ), phones AS (
+                    SELECT DISTINCT p.contact_id, right(regexp_replace(p.number, '\D', '', 'g'), 9) AS digits
+                        FROM contact_phones p JOIN candidates c ON c.id = p.contact_id
+                ), email_names AS (
+                    SELECT DISTINCT e.contact_id,
+                            replace(lower(split_part(split_part(e.address, '@', 1), '+', 1)), '.', '') AS name
+                        FROM contact_emails e JOIN candidates c ON c.id = e.contact_id
+                        WHERE e.address LIKE '%@%'
+                )
+                SELECT a.contact_id as "contact!: ContactId", b.contact_id as "other!: ContactId"
+                    FROM phones a JOIN phones b ON b.digits = a.digits AND b.contact_id > a.contact_id
+                    WHERE length(a.digits) >= 6
+                UNION
+                SELECT a.contact_id, b.contact_id
+                    FROM email_names a JOIN email_names b ON b.name = a.name AND b.contact_id > a.contact_id
+                    WHERE a.name <> ''
+                UNION
+                SELECT a.id, b.id
+                    FROM candidates a CROSS JOIN LATERAL (
+                        SELECT id FROM Contacts
+                            WHERE (first || ' ' || last) % a.name
+                              AND book_id = $1 AND deleted_at IS NULL AND id > a.id
+                    ) b"#,
+            book as BookId
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let ids: Vec<ContactId> = pairs
+            .iter()
+            .flat_map(|pair| [pair.contact, pair.other])
+            .collect();
+        let contacts: HashMap<Uuid, Contact> = self
+            .get_by_ids(book, &ids)
+            .await?
+            .into_iter()
+            .map(|contact| (contact.id.0, contact))
+            .collect();
+        let fingerprints: HashMap<Uuid, Fingerprint> = contacts
+            .iter()
+            .map(|(id, contact)| (*id, Fingerprint::from(contact)))
+            .collect();
+        let mut duplicates = Vec::new();
+        for pair in pairs {
+            let (Some(contact), Some(other)) =
+                (contacts.get(&pair.contact.0), contacts.get(&pair.other.0))
+            else {
+                continue;
+            };
+            let (score, reasons) =
+                fingerprints[&pair.contact.0].score(&fingerprints[&pair.other.0]);
+            if score >= LIKELY_DUPLICATE {
+                duplicates.push(Duplicate {
+                    contact: contact.clone(),
+                    other: other.clone(),
+                    score,
+                    reasons,
+                });
+            }
+        }
+        duplicates.sort_by_key(|duplicate| Reverse(duplicate.score));
+        Ok(duplicates)
+    }
+
+    /// The contacts in `book` that `candidate` is likely a duplicate of, most likely first.
+    /// Only contacts that share a phone number or email name or have a similar name are scored.
+    pub async fn get_duplicates_of(
+        &self,
+        book: BookId,
+        candidate: &ContactCandidate,
+    ) -> Result<Vec<Contact>> {
+        let fingerprint = Fingerprint::new(
+            &candidate.first,
+            &candidate.last,
+            &candidate.phones(),
+            &candidate.emails(),
+        );
+        let mut tx = self.db.begin().await?;
+        set_fuzzy_threshold(&mut tx).await?;
+        let rows = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts
+                WHERE book_id = $1 AND deleted_at IS NULL AND (
+                    (first || ' ' || last) % $2
+                    OR EXISTS (
+                        SELECT 1 FROM contact_phones p WHERE p.contact_id = Contacts.id
+                          AND right(regexp_replace(p.number, '\D', '', 'g'), 9) = ANY($3)
+                    )
+                    OR EXISTS (
+                        SELECT 1 FROM contact_emails e WHERE e.contact_id = Contacts.id
+                          AND e.address LIKE '%@%'
+                          AND replace(lower(split_part(split_part(e.address, '@', 1), '+', 1)), '.', '') = ANY($4)
+                    )
+                )"#,
+            book as BookId,
+            format!("{} {}", candidate.first, candidate.last),
+            &fingerprint.phones,
+            &fingerprint.email_names
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        let mut duplicates: Vec<(u32, Contact)> = details::attach(&self.db, rows)
+            .await?
+            .into_iter()
+            .filter_map(|contact| {
+                let (score, _) = fingerprint.score(&Fingerprint::from(&contact));
+                (score >= LIKELY_DUPLICATE).then_some((score, contact))
+            })
+            .collect();
+        duplicates.sort_by_key(|(score, _)| Reverse(*score));
+        Ok(duplicates.into_iter().map(|(_, contact)| contact).collect())
+    }
+
+    /// The contacts in `book` with one of the `ids`, in no particular order.
+    async fn get_by_ids(&self, book: BookId, ids: &[ContactId]) -> Result<Vec<Contact>> {
+        let rows = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts WHERE id = ANY($1) AND book_id = $2 AND deleted_at IS NULL"#,
+            ids as &[ContactId],
+            book as BookId
         )
         .fetch_all(&self.db)
         .await?;
-        Ok(contacts)
+        Ok(details::attach(&self.db, rows).await?)
     }
 
-    pub async fn delete_by_id(&self, id: ContactId) -> Result<()> {
-        sqlx::query!("DELETE FROM Contacts WHERE id = $1", id as ContactId)
-            .execute(&self.db)
-            .await?;
-        Ok(())
+    /// Marks the contact as a favourite or not. Returns whether there was such a contact.
+    pub async fn set_starred(&self, book: BookId, id: ContactId, starred: bool) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE Contacts SET starred = $1 WHERE id = $2 AND book_id = $3 AND deleted_at IS NULL",
+            starred,
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
     }
 
-    pub async fn create(&self, new_contact: &ContactCandidate) -> Result<ContactId> {
-        let result = sqlx::query_scalar!(
-        r#"INSERT INTO Contacts (first, last, phone, email) VALUES ($1, $2, $3, $4) RETURNING id as "id: ContactId""#,
-        new_contact.first,
-        new_contact.last,
-        new_contact.phone,
-        new_contact.email,
-    )
-    .fetch_one(&self.db)
-    .await;
-        match result {
-            Ok(result) => Ok(result),
-            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
-                Err(Error::DuplicateEmail)
+    /// All members of `organization`, by name.
+    pub async fn get_by_organization(
+        &self,
+        book: BookId,
+        organization: OrganizationId,
+    ) -> Result<Vec<Contact>> {
+        let rows = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts
+                WHERE book_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+                ORDER BY sort_first, sort_last, id"#,
+            book as BookId,
+            organization as OrganizationId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(details::attach(&self.db, rows).await?)
+    }
+
+    /// The birthdays and anniversaries in `book` from `today` up to `days` days later,
+    /// soonest first and then by name.
+    pub async fn get_upcoming(
+        &self,
+        book: BookId,
+        today: NaiveDate,
+        days: i64,
+    ) -> Result<Vec<Upcoming>> {
+        let rows = sqlx::query!(
+            r#"SELECT c.id, c.prefix, c.first, c.middle, c.last, c.suffix, c.birthday, c.anniversary,
+                    b.name_format as "name_format: NameFormat",
+                    CASE WHEN b.name_format = 'last_first' THEN c.sort_last ELSE c.sort_first END
+                        as "sort_key!",
+                    CASE WHEN b.name_format = 'last_first' THEN c.sort_first ELSE c.sort_last END
+                        as "sort_tiebreak!"
+                FROM Contacts c JOIN address_books b ON b.id = c.book_id
+                WHERE c.book_id = $1 AND c.deleted_at IS NULL
+                  AND (c.birthday IS NOT NULL OR c.anniversary IS NOT NULL)"#,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        let mut upcoming = Vec::new();
+        for row in rows {
+            let name = row.name_format.full_name(
+                &row.prefix,
+                &row.first,
+                &row.middle,
+                &row.last,
+                &row.suffix,
+            );
+            let occasions = [
+                (Occasion::Birthday, row.birthday),
+                (Occasion::Anniversary, row.anniversary),
+            ];
+            for (occasion, date) in occasions {
+                let Some(date) = date.and_then(|date| date.parse::<PartialDate>().ok()) else {
+                    continue;
+                };
+                let next = date.next_occurrence(today);
+                let days_until = (next - today).num_days();
+                if days_until <= days {
+                    upcoming.push((
+                        (days_until, row.sort_key.clone(), row.sort_tiebreak.clone()),
+                        Upcoming {
+                            id: row.id.into(),
+                            name: name.clone(),
+                            occasion,
+                            date: next,
+                            days: days_until,
+                            years: date.years_at(next),
+                        },
+                    ));
+                }
             }
-            Err(err) => Err(err)?,
         }
+        upcoming.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(upcoming.into_iter().map(|(_, upcoming)| upcoming).collect())
+    }
+
+    /// The names of up to ten tags of contacts in `book` starting with `prefix`.
+    pub async fn get_tags_by_prefix(&self, book: BookId, prefix: &str) -> Result<Vec<String>> {
+        let tags = sqlx::query_scalar!(
+            "SELECT DISTINCT t.name FROM tags t
+                JOIN contact_tags ct ON ct.tag_id = t.id
+                JOIN Contacts c ON c.id = ct.contact_id
+                WHERE c.book_id = $1 AND c.deleted_at IS NULL AND t.name ILIKE $2
+                ORDER BY t.name LIMIT 10",
+            book as BookId,
+            query::starts_with(prefix)
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(tags)
+    }
+
+    async fn attach_one(&self, row: Option<ContactRow>) -> Result<Option<Contact>> {
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(details::attach(&self.db, vec![row]).await?.pop())
+    }
+
+    /// Moves the contact to the trash, from where it can be restored until it is purged.
+    pub async fn delete_by_id(&self, book: BookId, id: ContactId) -> Result<()> {
+        let Some(contact) = self.get_by_id(book, id).await? else {
+            return Ok(());
+        };
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "UPDATE Contacts SET deleted_at = now() WHERE id = $1 AND book_id = $2 AND deleted_at IS NULL",
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        revisions::record(
+            &mut tx,
+            id,
+            RevisionAction::Delete,
+            &ContactCandidate::from(&contact),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The contacts in the trash of `book`, most recently deleted first.
+    pub async fn get_trash(&self, book: BookId) -> Result<Vec<Contact>> {
+        let rows = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts
+                WHERE book_id = $1 AND deleted_at IS NOT NULL
+                ORDER BY deleted_at DESC, id"#,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(details::attach(&self.db, rows).await?)
+    }
+
+    /// Takes the contact out of the trash again.
+    pub async fn restore_by_id(&self, book: BookId, id: ContactId) -> Result<()> {
+        let row = sqlx::query_as!(
+            ContactRow,
+            r#"SELECT id, book_id, first, last, prefix, middle, suffix, nickname, phonetic_first, phonetic_last, notes, custom as "custom: Json<CustomValues>", has_photo,
+                    birthday, anniversary, organization_id as "organization_id: OrganizationId",
+                    job_title, department, deleted_at, version, starred
+                FROM Contacts WHERE id = $1 AND book_id = $2 AND deleted_at IS NOT NULL"#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        let Some(contact) = self.attach_one(row).await? else {
+            return Ok(());
+        };
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "UPDATE Contacts SET deleted_at = NULL WHERE id = $1 AND book_id = $2",
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        revisions::record(
+            &mut tx,
+            id,
+            RevisionAction::Restore,
+            &ContactCandidate::from(&contact),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes a contact in the trash for good, along with its relations to other contacts.
+    /// Returns `false` if there is no such contact in the trash.
+    pub async fn purge_by_id(&self, book: BookId, id: ContactId) -> Result<bool> {
+        let deleted = sqlx::query!(
+            "DELETE FROM Contacts WHERE id = $1 AND book_id = $2 AND deleted_at IS NOT NULL",
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Deletes all contacts that went to the trash before `before` for good.
+    /// Returns the ids of the deleted contacts.
+    pub async fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<Vec<ContactId>> {
+        let ids = sqlx::query_scalar!(
+            r#"DELETE FROM Contacts WHERE deleted_at < $1 RETURNING id as "id: ContactId""#,
+            before
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(ids)
     }
 
+    pub async fn create(&self, book: BookId, new_contact: &ContactCandidate) -> Result<ContactId> {
+        new_contact.validate().map_err(Error::InvalidContact)?;
+        let custom = self.parse_custom(book, new_contact).await?;
+        let birthday = new_contact.birthday()?.map(|date| date.to_string());
+        let anniversary = new_contact.anniversary()?.map(|date| date.to_string());
+        let mut tx = self.db.begin().await?;
+        let organization =
+            organizations::find_or_create(&mut tx, book, &new_contact.organization).await?;
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO Contacts (book_id, first, last, notes, custom, birthday, anniversary, organization_id, job_title, department,
+                    prefix, middle, suffix, nickname, phonetic_first, phonetic_last)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                RETURNING id as "id: ContactId""#,
+            book as BookId,
+            new_contact.first,
+            new_contact.last,
+            new_contact.notes,
+            Json(&custom) as _,
+            birthday,
+            anniversary,
+            organization as Option<OrganizationId>,
+            new_contact.job_title.trim(),
+            new_contact.department.trim(),
+            new_contact.prefix.trim(),
+            new_contact.middle.trim(),
+            new_contact.suffix.trim(),
+            new_contact.nickname.trim(),
+            new_contact.phonetic_first.trim(),
+            new_contact.phonetic_last.trim(),
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        details::store(&mut tx, id, new_contact)
+            .await
+            .map_err(unique_violation_to_duplicate_email)?;
+        revisions::record(
+            &mut tx,
+            id,
+            RevisionAction::Create,
+            &new_contact.normalized(&custom)?,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Updates the contact, failing with [`Error::InvalidContact`] if any value is invalid and with
+    /// [`Error::EditConflict`] if `new_contact.version` is given and the contact has been updated
+    /// since then.
     pub async fn update_by_id(
         &self,
+        book: BookId,
         id: ContactId,
         new_contact: &ContactCandidate,
     ) -> Result<ContactId> {
-        let result = sqlx::query_scalar!(
-    r#"UPDATE Contacts SET first = $1, last = $2, phone = $3, email = $4 WHERE id = $5 RETURNING id as "id: ContactId""#,
-    new_contact.first,
-    new_contact.last,
-    new_contact.phone,
-    new_contact.email,
-    id as ContactId
-)
-    .fetch_one(&self.db)
-    .await;
-        match result {
-            Ok(result) => Ok(result),
-            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
-                Err(Error::DuplicateEmail)
-            }
-            Err(err) => Err(err)?,
+        let mut tx = self.db.begin().await?;
+        let id = self
+            .update(&mut tx, book, id, new_contact, RevisionAction::Update)
+            .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Merges the contacts `merged` into contact `id`, which ends up as `new_contact`.
+    /// The relations, interactions and reminders of the merged contacts move over to it before
+    /// they are deleted for good, while what they looked like is kept in a merge record.
+    pub async fn merge(
+        &self,
+        book: BookId,
+        id: ContactId,
+        merged: &[ContactId],
+        new_contact: &ContactCandidate,
+    ) -> Result<ContactId> {
+        if merged.is_empty() || merged.contains(&id) {
+            return Err(Error::InvalidMerge);
         }
+        let mut snapshots = Vec::new();
+        for &other in merged {
+            let Some(contact) = self.get_by_id(book, other).await? else {
+                return Err(Error::InvalidMerge);
+            };
+            snapshots.push(ContactCandidate::from(&contact));
+        }
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "INSERT INTO contact_relations (contact_id, related_id, kind)
+                SELECT contact_id, related_id, kind FROM (
+                    SELECT CASE WHEN contact_id = ANY($2) THEN $1 ELSE contact_id END AS contact_id,
+                           CASE WHEN related_id = ANY($2) THEN $1 ELSE related_id END AS related_id,
+                           kind
+                        FROM contact_relations WHERE contact_id = ANY($2) OR related_id = ANY($2)
+                ) r
+                WHERE contact_id <> related_id
+                ON CONFLICT DO NOTHING",
+            id as ContactId,
+            merged as &[ContactId]
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE interactions SET contact_id = $1 WHERE contact_id = ANY($2)",
+            id as ContactId,
+            merged as &[ContactId]
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE reminders SET contact_id = $1 WHERE contact_id = ANY($2)",
+            id as ContactId,
+            merged as &[ContactId]
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE Contacts SET starred = true
+                WHERE id = $1 AND EXISTS (SELECT 1 FROM Contacts WHERE id = ANY($2) AND starred)",
+            id as ContactId,
+            merged as &[ContactId]
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO contact_merges (contact_id, merged_ids, merged) VALUES ($1, $2, $3)",
+            id as ContactId,
+            merged as &[ContactId],
+            Json(&snapshots) as _
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM Contacts WHERE id = ANY($1) AND book_id = $2",
+            merged as &[ContactId],
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        let id = self
+            .update(&mut tx, book, id, new_contact, RevisionAction::Merge)
+            .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Updates the contact as part of the transaction `conn`, recording a revision with `action`.
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        book: BookId,
+        id: ContactId,
+        new_contact: &ContactCandidate,
+        action: RevisionAction,
+    ) -> Result<ContactId> {
+        new_contact.validate().map_err(Error::InvalidContact)?;
+        let custom = self.parse_custom(book, new_contact).await?;
+        let birthday = new_contact.birthday()?.map(|date| date.to_string());
+        let anniversary = new_contact.anniversary()?.map(|date| date.to_string());
+        let organization =
+            organizations::find_or_create(&mut *conn, book, &new_contact.organization).await?;
+        let id = sqlx::query_scalar!(
+            r#"UPDATE Contacts SET first = $1, last = $2, notes = $5, custom = $6, birthday = $7, anniversary = $8,
+                    organization_id = $9, job_title = $10, department = $11, prefix = $13, middle = $14,
+                    suffix = $15, nickname = $16, phonetic_first = $17, phonetic_last = $18,
+                    version = version + 1
+                WHERE id = $3 AND book_id = $4 AND deleted_at IS NULL
+                  AND ($12::INTEGER IS NULL OR version = $12)
+                RETURNING id as "id: ContactId""#,
+            new_contact.first,
+            new_contact.last,
+            id as ContactId,
+            book as BookId,
+            new_contact.notes,
+            Json(&custom) as _,
+            birthday,
+            anniversary,
+            organization as Option<OrganizationId>,
+            new_contact.job_title.trim(),
+            new_contact.department.trim(),
+            new_contact.version,
+            new_contact.prefix.trim(),
+            new_contact.middle.trim(),
+            new_contact.suffix.trim(),
+            new_contact.nickname.trim(),
+            new_contact.phonetic_first.trim(),
+            new_contact.phonetic_last.trim(),
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(id) = id else {
+            return Err(match new_contact.version {
+                Some(_) => Error::EditConflict,
+                None => sqlx::Error::RowNotFound.into(),
+            });
+        };
+        details::store(&mut *conn, id, new_contact)
+            .await
+            .map_err(unique_violation_to_duplicate_email)?;
+        revisions::record(&mut *conn, id, action, &new_contact.normalized(&custom)?).await?;
+        Ok(id)
+    }
+
+    /// Validates the custom field values of `candidate` against the fields of `book`.
+    async fn parse_custom(
+        &self,
+        book: BookId,
+        candidate: &ContactCandidate,
+    ) -> Result<CustomValues> {
+        let fields = self.custom_fields().get_all(book).await?;
+        fields::parse_values(&fields, &candidate.custom()).map_err(Error::InvalidCustomFields)
     }
 }
 
-#[derive(Deserialize)]
+/// Lowers the similarity threshold of the `%` operator for the rest of the transaction.
+async fn set_fuzzy_threshold(conn: &mut PgConnection) -> sqlx::Result<()> {
+    sqlx::query!(
+        "SELECT set_config('pg_trgm.similarity_threshold', $1, true)",
+        FUZZY_THRESHOLD.to_string()
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(())
+}
+
+fn unique_violation_to_duplicate_email(err: sqlx::Error) -> Error {
+    match err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => Error::DuplicateEmail,
+        err => err.into(),
+    }
+}
+
+/// A field that differs between two versions of a contact.
+pub struct Change {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ContactCandidate {
     pub first: String,
     pub last: String,
-    pub phone: String,
-    pub email: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub middle: String,
+    #[serde(default)]
+    pub suffix: String,
+    #[serde(default)]
+    pub nickname: String,
+    #[serde(default)]
+    pub phonetic_first: String,
+    #[serde(default)]
+    pub phonetic_last: String,
+    #[serde(default)]
+    pub phone_label: Vec<String>,
+    #[serde(default)]
+    pub phone: Vec<String>,
+    #[serde(default)]
+    pub email_label: Vec<String>,
+    #[serde(default)]
+    pub email: Vec<String>,
+    #[serde(default)]
+    pub address_label: Vec<String>,
+    #[serde(default)]
+    pub address_street: Vec<String>,
+    #[serde(default)]
+    pub address_locality: Vec<String>,
+    #[serde(default)]
+    pub address_region: Vec<String>,
+    #[serde(default)]
+    pub address_postal_code: Vec<String>,
+    #[serde(default)]
+    pub address_country: Vec<String>,
+    #[serde(default)]
+    pub tag: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub organization: String,
+    #[serde(default)]
+    pub job_title: String,
+    #[serde(default)]
+    pub department: String,
+    #[serde(default)]
+    pub birthday: String,
+    #[serde(default)]
+    pub anniversary: String,
+    #[serde(default)]
+    pub custom_field: Vec<String>,
+    #[serde(default)]
+    pub custom_value: Vec<String>,
+    /// The version of the contact the edit is based on. Not part of revision snapshots.
+    #[serde(default, skip_serializing)]
+    pub version: Option<i32>,
+}
+
+impl ContactCandidate {
+    /// The non-empty phone numbers of the form, in order. The first one is the primary one.
+    pub fn phones(&self) -> Vec<Phone> {
+        self.phone_label
+            .iter()
+            .zip(&self.phone)
+            .filter(|(_, number)| !number.trim().is_empty())
+            .enumerate()
+            .map(|(i, (label, number))| Phone {
+                label: label.trim().to_owned(),
+                number: number.trim().to_owned(),
+                primary: i == 0,
+            })
+            .collect()
+    }
+
+    /// The non-empty emails of the form, in order. The first one is the primary one.
+    pub fn emails(&self) -> Vec<Email> {
+        self.email_label
+            .iter()
+            .zip(&self.email)
+            .filter(|(_, address)| !address.trim().is_empty())
+            .enumerate()
+            .map(|(i, (label, address))| Email {
+                label: label.trim().to_owned(),
+                address: address.trim().to_owned(),
+                primary: i == 0,
+            })
+            .collect()
+    }
+
+    /// The addresses of the form that have at least one non-empty part, in order.
+    pub fn addresses(&self) -> Vec<Address> {
+        let part = |parts: &[String], i: usize| {
+            parts
+                .get(i)
+                .map(|part| part.trim().to_owned())
+                .unwrap_or_default()
+        };
+        (0..self.address_label.len())
+            .map(|i| Address {
+                label: part(&self.address_label, i),
+                street: part(&self.address_street, i),
+                locality: part(&self.address_locality, i),
+                region: part(&self.address_region, i),
+                postal_code: part(&self.address_postal_code, i),
+                country: part(&self.address_country, i),
+            })
+            .filter(|address| !address.is_empty())
+            .collect()
+    }
+
+    /// The distinct, non-empty tags of the form, lowercased.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .tag
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// The birthday of the form, if one was entered.
+    pub fn birthday(&self) -> Result<Option<PartialDate>> {
+        parse_date(&self.birthday, Occasion::Birthday)
+    }
+
+    /// The anniversary of the form, if one was entered.
+    pub fn anniversary(&self) -> Result<Option<PartialDate>> {
+        parse_date(&self.anniversary, Occasion::Anniversary)
+    }
+
+    /// The candidate the way it ends up stored: trimmed, without empty rows and with the
+    /// `custom` values that passed validation.
+    fn normalized(&self, custom: &CustomValues) -> Result<ContactCandidate> {
+        let phones = self.phones();
+        let emails = self.emails();
+        let addresses = self.addresses();
+        let column = |f: fn(&Address) -> &String| addresses.iter().map(|a| f(a).clone()).collect();
+        Ok(ContactCandidate {
+            first: self.first.clone(),
+            last: self.last.clone(),
+            prefix: self.prefix.trim().to_owned(),
+            middle: self.middle.trim().to_owned(),
+            suffix: self.suffix.trim().to_owned(),
+            nickname: self.nickname.trim().to_owned(),
+            phonetic_first: self.phonetic_first.trim().to_owned(),
+            phonetic_last: self.phonetic_last.trim().to_owned(),
+            phone_label: phones.iter().map(|phone| phone.label.clone()).collect(),
+            phone: phones.iter().map(|phone| phone.number.clone()).collect(),
+            email_label: emails.iter().map(|email| email.label.clone()).collect(),
+            email: emails.iter().map(|email| email.address.clone()).collect(),
+            address_label: column(|address| &address.label),
+            address_street: column(|address| &address.street),
+            address_locality: column(|address| &address.locality),
+            address_region: column(|address| &address.region),
+            address_postal_code: column(|address| &address.postal_code),
+            address_country: column(|address| &address.country),
+            tag: self.tags(),
+            notes: self.notes.clone(),
+            organization: self.organization.trim().to_owned(),
+            job_title: self.job_title.trim().to_owned(),
+            department: self.department.trim().to_owned(),
+            birthday: self
+                .birthday()?
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            anniversary: self
+                .anniversary()?
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            custom_field: custom.keys().cloned().collect(),
+            custom_value: custom.values().map(format_value).collect(),
+            version: None,
+        })
+    }
+
+    /// Every field as a label and a human readable value, with custom fields named after `fields`.
+    pub fn summary(&self, fields: &[CustomField]) -> Vec<(String, String)> {
+        let join = |values: Vec<String>| values.join("; ");
+        let mut summary = vec![
+            (String::from("First Name"), self.first.clone()),
+            (String::from("Last Name"), self.last.clone()),
+            (String::from("Prefix"), self.prefix.clone()),
+            (String::from("Middle Name"), self.middle.clone()),
+            (String::from("Suffix"), self.suffix.clone()),
+            (String::from("Nickname"), self.nickname.clone()),
+            (
+                String::from("Phonetic First Name"),
+                self.phonetic_first.clone(),
+            ),
+            (
+                String::from("Phonetic Last Name"),
+                self.phonetic_last.clone(),
+            ),
+            (
+                String::from("Phones"),
+                join(
+                    self.phones()
+                        .iter()
+                        .map(|phone| format!("{}: {}", phone.label, phone.number))
+                        .collect(),
+                ),
+            ),
+            (
+                String::from("Emails"),
+                join(
+                    self.emails()
+                        .iter()
+                        .map(|email| format!("{}: {}", email.label, email.address))
+                        .collect(),
+                ),
+            ),
+            (
+                String::from("Addresses"),
+                join(
+                    self.addresses()
+                        .iter()
+                        .map(|address| format!("{}: {}", address.label, address.one_line()))
+                        .collect(),
+                ),
+            ),
+            (String::from("Tags"), self.tags().join(", ")),
+            (String::from("Company"), self.organization.clone()),
+            (String::from("Job Title"), self.job_title.clone()),
+            (String::from("Department"), self.department.clone()),
+            (String::from("Birthday"), self.birthday.clone()),
+            (String::from("Anniversary"), self.anniversary.clone()),
+            (String::from("Notes"), self.notes.clone()),
+        ];
+        for (id, value) in self.custom() {
+            let name = fields
+                .iter()
+                .find(|field| field.id.to_string() == id)
+                .map_or(id, |field| field.name.clone());
+            summary.push((name, value));
+        }
+        summary
+    }
+
+    /// The fields that differ from `before`, or all non-empty fields if there is nothing before.
+    pub fn changes(
+        &self,
+        before: Option<&ContactCandidate>,
+        fields: &[CustomField],
+    ) -> Vec<Change> {
+        let before = before
+            .map(|before| before.summary(fields))
+            .unwrap_or_default();
+        let after = self.summary(fields);
+        let value = |summary: &[(String, String)], field: &str| {
+            summary
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        let mut changes: Vec<Change> = Vec::new();
+        for (field, _) in after.iter().chain(&before) {
+            if changes.iter().any(|change| &change.field == field) {
+                continue;
+            }
+            let (before, after) = (value(&before, field), value(&after, field));
+            if before != after {
+                changes.push(Change {
+                    field: field.clone(),
+                    before,
+                    after,
+                });
+            }
+        }
+        changes
+    }
+
+    /// The fields that someone else changed from `base` to `theirs` while this edit was made,
+    /// with their value as `before` and the value of this edit as `after`.
+    pub fn conflicts(
+        &self,
+        base: &ContactCandidate,
+        theirs: &ContactCandidate,
+        fields: &[CustomField],
+    ) -> Vec<Change> {
+        let mine = self.summary(fields);
+        theirs
+            .changes(Some(base), fields)
+            .into_iter()
+            .map(|change| Change {
+                after: mine
+                    .iter()
+                    .find(|(name, _)| *name == change.field)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default(),
+                before: change.after,
+                field: change.field,
+            })
+            .collect()
+    }
+
+    /// The submitted custom field values, keyed by the field id.
+    pub fn custom(&self) -> HashMap<String, String> {
+        self.custom_field
+            .iter()
+            .cloned()
+            .zip(self.custom_value.iter().cloned())
+            .collect()
+    }
+}
+
+fn parse_date(raw: &str, occasion: Occasion) -> Result<Option<PartialDate>> {
+    if raw.trim().is_empty() {
+        return Ok(None);
+    }
+    raw.parse()
+        .map(Some)
+        .map_err(|_| Error::InvalidDate(occasion))
+}
+
+impl From<&Contact> for ContactCandidate {
+    fn from(contact: &Contact) -> Self {
+        let column = |f: fn(&Address) -> &String| {
+            contact
+                .addresses
+                .iter()
+                .map(|address| f(address).clone())
+                .collect()
+        };
+        Self {
+            first: contact.first.clone(),
+            last: contact.last.clone(),
+            prefix: contact.prefix.clone(),
+            middle: contact.middle.clone(),
+            suffix: contact.suffix.clone(),
+            nickname: contact.nickname.clone(),
+            phonetic_first: contact.phonetic_first.clone(),
+            phonetic_last: contact.phonetic_last.clone(),
+            phone_label: contact
+                .phones
+                .iter()
+                .map(|phone| phone.label.clone())
+                .collect(),
+            phone: contact
+                .phones
+                .iter()
+                .map(|phone| phone.number.clone())
+                .collect(),
+            email_label: contact
+                .emails
+                .iter()
+                .map(|email| email.label.clone())
+                .collect(),
+            email: contact
+                .emails
+                .iter()
+                .map(|email| email.address.clone())
+                .collect(),
+            address_label: column(|address| &address.label),
+            address_street: column(|address| &address.street),
+            address_locality: column(|address| &address.locality),
+            address_region: column(|address| &address.region),
+            address_postal_code: column(|address| &address.postal_code),
+            address_country: column(|address| &address.country),
+            tag: contact.tags.clone(),
+            notes: contact.notes.clone(),
+            organization: contact
+                .organization
+                .as_ref()
+                .map(|organization| organization.name.clone())
+                .unwrap_or_default(),
+            job_title: contact.job_title.clone(),
+            department: contact.department.clone(),
+            birthday: contact
+                .birthday
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            anniversary: contact
+                .anniversary
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            custom_field: contact.custom.keys().cloned().collect(),
+            custom_value: contact.custom.values().map(format_value).collect(),
+            version: Some(contact.version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::model::{AddressBooks, CustomFields, FieldCandidate, FieldKind};
+
+    async fn setup(db: &PgPool) -> (Contacts, BookId) {
+        let book = AddressBooks::new(db.clone()).create("Test").await.unwrap();
+        (Contacts::new(db.clone()), book)
+    }
+
+    async fn create(contacts: &Contacts, book: BookId, candidate: ContactCandidate) -> ContactId {
+        contacts.create(book, &candidate).await.unwrap()
+    }
+
+    fn named(first: &str, last: &str) -> ContactCandidate {
+        ContactCandidate {
+            first: first.to_owned(),
+            last: last.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    async fn search(contacts: &Contacts, book: BookId, search_term: &str) -> Vec<ContactId> {
+        contacts
+            .get_filtered_page(book, search_term, None, false, ContactOrder::default(), 1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|contact| contact.id)
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn ranks_name_matches_before_notes(db: PgPool) {
+        let (contacts, book) = setup(&db).await;
+        let noted = create(
+            &contacts,
+            book,
+            ContactCandidate {
+                notes: String::from("Met at the Lovelace lecture"),
+                ..named("Charles", "Babbage")
+            },
+        )
+        .await;
+        let ada = create(&contacts, book, named("Ada", "Lovelace")).await;
+
+        assert_eq!(search(&contacts, book, "lovelace").await, [ada, noted]);
+        assert_eq!(search(&contacts, book, "lovel").await, [ada, noted]);
+        assert_eq!(search(&contacts, book, "lovelace -babbage").await, [ada]);
+
+        let by_name = ContactOrder {
+            sort: Some(ContactSort::Name),
+            direction: SortDirection::Desc,
+        };
+        let found: Vec<ContactId> = contacts
+            .get_filtered_page(book, "lovelace", None, false, by_name, 1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|contact| contact.id)
+            .collect();
+        assert_eq!(found, [noted, ada]);
+    }
+
+    #[sqlx::test]
+    async fn falls_back_to_similar_names(db: PgPool) {
+        let (contacts, book) = setup(&db).await;
+        let ada = create(&contacts, book, named("Ada", "Lovelace")).await;
+        create(&contacts, book, named("Charles", "Babbage")).await;
+
+        assert!(search(&contacts, book, "Ada Lovleace").await.is_empty());
+        assert_eq!(
+            contacts
+                .get_name_suggestions(book, "Ada Lovleace")
+                .await
+                .unwrap(),
+            ["Ada Lovelace"]
+        );
+        let found: Vec<ContactId> = contacts
+            .get_fuzzy_page(
+                book,
+                "Ada Lovleace",
+                None,
+                false,
+                ContactOrder::default(),
+                1,
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|contact| contact.id)
+            .collect();
+        assert_eq!(found, [ada]);
+    }
+
+    #[sqlx::test]
+    async fn ignores_accents_and_transliterations(db: PgPool) {
+        let (contacts, book) = setup(&db).await;
+        let field = CustomFields::new(db.clone())
+            .create(
+                book,
+                &FieldCandidate {
+                    name: String::from("Motto"),
+                    kind: FieldKind::Text,
+                    options: String::new(),
+                },
+            )
+            .await
+            .unwrap();
+        let muller = create(
+            &contacts,
+            book,
+            ContactCandidate {
+                email_label: vec![String::new()],
+                email: vec![String::from("muller@example.com")],
+                custom_field: vec![field.to_string()],
+                custom_value: vec![String::from("“Grüße” aus Köln")],
+                ..named("Jürgen", "Müller")
+            },
+        )
+        .await;
+
+        for search_term in ["muller", "Mueller", "MÜLLER", "jurg", "first:Juergen"] {
+            assert_eq!(search(&contacts, book, search_term).await, [muller]);
+        }
+        assert_eq!(search(&contacts, book, "grusse koln").await, [muller]);
+
+        let found = contacts
+            .get_by_email(book, "Muller@Example.com")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|contact| contact.id), Some(muller));
+        let found = contacts
+            .get_by_email(book, "mueller@example.com")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|contact| contact.id), Some(muller));
+    }
 }
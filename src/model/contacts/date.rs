@@ -0,0 +1,88 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, NaiveDate};
+
+/// A calendar date whose year may be unknown, like a birthday of which only the day is known.
+///
+/// Written as `YYYY-MM-DD`, or as `--MM-DD` without a year.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PartialDate {
+    pub year: Option<i32>,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl PartialDate {
+    /// The next time this date comes around, counting `today` itself.
+    /// February 29th falls on February 28th in years without one.
+    pub fn next_occurrence(&self, today: NaiveDate) -> NaiveDate {
+        let in_year = |year| {
+            NaiveDate::from_ymd_opt(year, self.month, self.day)
+                .or_else(|| NaiveDate::from_ymd_opt(year, self.month, self.day - 1))
+                .expect("partial dates are always valid in leap years")
+        };
+        let this_year = in_year(today.year());
+        if this_year >= today {
+            this_year
+        } else {
+            in_year(today.year() + 1)
+        }
+    }
+
+    /// How many years have passed since this date at `on`, if the year is known.
+    pub fn years_at(&self, on: NaiveDate) -> Option<i32> {
+        self.year.map(|year| on.year() - year)
+    }
+
+    /// The date in words, e.g. "March 5, 1990" or "March 5".
+    pub fn long(&self) -> String {
+        let month = NaiveDate::from_ymd_opt(2000, self.month, 1)
+            .expect("month is always valid")
+            .format("%B");
+        match self.year {
+            Some(year) => format!("{month} {}, {year}", self.day),
+            None => format!("{month} {}", self.day),
+        }
+    }
+}
+
+impl fmt::Display for PartialDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.year {
+            Some(year) => write!(f, "{year:04}-{:02}-{:02}", self.month, self.day),
+            None => write!(f, "--{:02}-{:02}", self.month, self.day),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidDate;
+
+impl FromStr for PartialDate {
+    type Err = InvalidDate;
+
+    /// Accepts `YYYY-MM-DD`, `--MM-DD` and `MM-DD`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Self {
+                year: Some(date.year()),
+                month: date.month(),
+                day: date.day(),
+            });
+        }
+        let (month, day) = s
+            .trim_start_matches("--")
+            .split_once('-')
+            .ok_or(InvalidDate)?;
+        let month = month.parse().map_err(|_| InvalidDate)?;
+        let day = day.parse().map_err(|_| InvalidDate)?;
+        // 2000 is a leap year, so February 29th is accepted.
+        NaiveDate::from_ymd_opt(2000, month, day).ok_or(InvalidDate)?;
+        Ok(Self {
+            year: None,
+            month,
+            day,
+        })
+    }
+}
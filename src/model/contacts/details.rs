@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{
+    types::{Json, Uuid},
+    PgConnection, PgPool,
+};
+
+use super::{Contact, ContactCandidate, ContactId};
+use crate::model::{BookId, CustomValues, NameFormat, Organization, OrganizationId};
+
+#[derive(Default, Clone, Debug)]
+pub struct Phone {
+    pub label: String,
+    pub number: String,
+    pub primary: bool,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct Email {
+    pub label: String,
+    pub address: String,
+    pub primary: bool,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct Address {
+    pub label: String,
+    pub street: String,
+    pub locality: String,
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+}
+
+impl Address {
+    /// All non-empty parts of the address, separated by commas.
+    pub fn one_line(&self) -> String {
+        let town = [self.postal_code.as_str(), self.locality.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        [self.street.as_str(), &town, &self.region, &self.country]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        [
+            &self.street,
+            &self.locality,
+            &self.region,
+            &self.postal_code,
+            &self.country,
+        ]
+        .iter()
+        .all(|part| part.is_empty())
+    }
+}
+
+/// The columns of the `Contacts` table itself, before the child rows are attached.
+#[derive(sqlx::FromRow)]
+pub(super) struct ContactRow {
+    pub id: ContactId,
+    pub book_id: BookId,
+    pub first: String,
+    pub last: String,
+    pub prefix: String,
+    pub middle: String,
+    pub suffix: String,
+    pub nickname: String,
+    pub phonetic_first: String,
+    pub phonetic_last: String,
+    pub notes: String,
+    pub custom: Json<CustomValues>,
+    pub has_photo: bool,
+    pub birthday: Option<String>,
+    pub anniversary: Option<String>,
+    pub organization_id: Option<OrganizationId>,
+    pub job_title: String,
+    pub department: String,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub version: i32,
+    pub starred: bool,
+}
+
+/// Loads the phone numbers, emails, addresses, tags, organizations, last interactions and name
+/// formats of all `rows` in one query per table.
+pub(super) async fn attach(db: &PgPool, rows: Vec<ContactRow>) -> sqlx::Result<Vec<Contact>> {
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.id.0).collect();
+    let index: HashMap<Uuid, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let organization_ids: Vec<OrganizationId> =
+        rows.iter().filter_map(|row| row.organization_id).collect();
+    let organizations: HashMap<OrganizationId, Organization> = sqlx::query_as!(
+        Organization,
+        "SELECT id, name, website, notes FROM organizations WHERE id = ANY($1)",
+        &organization_ids as &[OrganizationId]
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|organization| (organization.id, organization))
+    .collect();
+    let book_ids: Vec<BookId> = rows.iter().map(|row| row.book_id).collect();
+    let name_formats: HashMap<BookId, NameFormat> = sqlx::query!(
+        r#"SELECT id as "id: BookId", name_format as "name_format: NameFormat"
+            FROM address_books WHERE id = ANY($1)"#,
+        &book_ids as &[BookId]
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|book| (book.id, book.name_format))
+    .collect();
+
+    let mut contacts: Vec<Contact> = rows
+        .into_iter()
+        .map(|row| Contact {
+            id: row.id,
+            book: row.book_id,
+            first: row.first,
+            last: row.last,
+            prefix: row.prefix,
+            middle: row.middle,
+            suffix: row.suffix,
+            nickname: row.nickname,
+            phonetic_first: row.phonetic_first,
+            phonetic_last: row.phonetic_last,
+            phones: Vec::new(),
+            emails: Vec::new(),
+            addresses: Vec::new(),
+            tags: Vec::new(),
+            notes: row.notes,
+            custom: row.custom.0,
+            has_photo: row.has_photo,
+            birthday: row.birthday.and_then(|date| date.parse().ok()),
+            anniversary: row.anniversary.and_then(|date| date.parse().ok()),
+            organization: row
+                .organization_id
+                .and_then(|id| organizations.get(&id).cloned()),
+            job_title: row.job_title,
+            department: row.department,
+            deleted_at: row.deleted_at,
+            version: row.version,
+            starred: row.starred,
+            last_interaction: None,
+            name_format: name_formats.get(&row.book_id).copied().unwrap_or_default(),
+        })
+        .collect();
+
+    let phones = sqlx::query!(
+        "SELECT contact_id, label, number, is_primary FROM contact_phones
+            WHERE contact_id = ANY($1) ORDER BY position",
+        &ids
+    )
+    .fetch_all(db)
+    .await?;
+    for phone in phones {
+        contacts[index[&phone.contact_id]].phones.push(Phone {
+            label: phone.label,
+            number: phone.number,
+            primary: phone.is_primary,
+        });
+    }
+
+    let emails = sqlx::query!(
+        "SELECT contact_id, label, address, is_primary FROM contact_emails
+            WHERE contact_id = ANY($1) ORDER BY position",
+        &ids
+    )
+    .fetch_all(db)
+    .await?;
+    for email in emails {
+        contacts[index[&email.contact_id]].emails.push(Email {
+            label: email.label,
+            address: email.address,
+            primary: email.is_primary,
+        });
+    }
+
+    let addresses = sqlx::query!(
+        "SELECT contact_id, label, street, locality, region, postal_code, country
+            FROM contact_addresses WHERE contact_id = ANY($1) ORDER BY position",
+        &ids
+    )
+    .fetch_all(db)
+    .await?;
+    for address in addresses {
+        contacts[index[&address.contact_id]]
+            .addresses
+            .push(Address {
+                label: address.label,
+                street: address.street,
+                locality: address.locality,
+                region: address.region,
+                postal_code: address.postal_code,
+                country: address.country,
+            });
+    }
+
+    let tags = sqlx::query!(
+        "SELECT ct.contact_id, t.name FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+            WHERE ct.contact_id = ANY($1) ORDER BY t.name",
+        &ids
+    )
+    .fetch_all(db)
+    .await?;
+    for tag in tags {
+        contacts[index[&tag.contact_id]].tags.push(tag.name);
+    }
+
+    let interactions = sqlx::query!(
+        r#"SELECT contact_id, MAX(happened_on) as "last!: NaiveDate" FROM interactions
+            WHERE contact_id = ANY($1) GROUP BY contact_id"#,
+        &ids
+    )
+    .fetch_all(db)
+    .await?;
+    for interaction in interactions {
+        contacts[index[&interaction.contact_id]].last_interaction = Some(interaction.last);
+    }
+
+    Ok(contacts)
+}
+
+/// Replaces the phone numbers, emails, addresses and tags of contact `id` with the ones in `candidate`
+/// and refreshes what the contact can be searched by.
+pub(super) async fn store(
+    conn: &mut PgConnection,
+    id: ContactId,
+    candidate: &ContactCandidate,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "DELETE FROM contact_phones WHERE contact_id = $1",
+        id as ContactId
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM contact_emails WHERE contact_id = $1",
+        id as ContactId
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM contact_addresses WHERE contact_id = $1",
+        id as ContactId
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let (labels, numbers): (Vec<_>, Vec<_>) = candidate
+        .phones()
+        .into_iter()
+        .map(|phone| (phone.label, phone.number))
+        .unzip();
+    sqlx::query!(
+        "INSERT INTO contact_phones (contact_id, position, label, number, is_primary)
+            SELECT $1, position, label, number, position = 1
+            FROM UNNEST($2::TEXT[], $3::TEXT[]) WITH ORDINALITY AS t(label, number, position)",
+        id as ContactId,
+        &labels,
+        &numbers,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let (labels, addresses): (Vec<_>, Vec<_>) = candidate
+        .emails()
+        .into_iter()
+        .map(|email| (email.label, email.address))
+        .unzip();
+    sqlx::query!(
+        "INSERT INTO contact_emails (contact_id, book_id, position, label, address, is_primary)
+            SELECT c.id, c.book_id, position, label, address, position = 1
+            FROM Contacts c,
+                UNNEST($2::TEXT[], $3::TEXT[]) WITH ORDINALITY AS t(label, address, position)
+            WHERE c.id = $1",
+        id as ContactId,
+        &labels,
+        &addresses,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let addresses = candidate.addresses();
+    let column = |f: fn(&Address) -> &String| -> Vec<String> {
+        addresses.iter().map(|address| f(address).clone()).collect()
+    };
+    sqlx::query!(
+        "INSERT INTO contact_addresses
+            (contact_id, position, label, street, locality, region, postal_code, country)
+            SELECT $1, position, label, street, locality, region, postal_code, country
+            FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[], $6::TEXT[], $7::TEXT[])
+                WITH ORDINALITY AS t(label, street, locality, region, postal_code, country, position)",
+        id as ContactId,
+        &column(|address| &address.label),
+        &column(|address| &address.street),
+        &column(|address| &address.locality),
+        &column(|address| &address.region),
+        &column(|address| &address.postal_code),
+        &column(|address| &address.country),
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM contact_tags WHERE contact_id = $1",
+        id as ContactId
+    )
+    .execute(&mut *conn)
+    .await?;
+    let tags = candidate.tags();
+    sqlx::query!(
+        "INSERT INTO tags (name) SELECT UNNEST($1::TEXT[]) ON CONFLICT (name) DO NOTHING",
+        &tags
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO contact_tags (contact_id, tag_id)
+            SELECT $1, id FROM tags WHERE name = ANY($2)",
+        id as ContactId,
+        &tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    refresh_search(conn, &[id]).await
+}
+
+/// Copies the phone numbers, emails, addresses, tags and company of the contacts `ids` into their
+/// `search_details`, which feeds the full text `search` column. Phone numbers are added with
+/// digits only and emails split into their parts, so that those can be searched for as well.
+pub(in crate::model) async fn refresh_search(
+    conn: &mut PgConnection,
+    ids: &[ContactId],
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r"UPDATE Contacts c SET search_details = CONCAT_WS(' ',
+            (SELECT o.name FROM organizations o WHERE o.id = c.organization_id),
+            (SELECT string_agg(p.number || ' ' || regexp_replace(p.number, '\D', '', 'g'), ' ')
+                FROM contact_phones p WHERE p.contact_id = c.id),
+            (SELECT string_agg(
+                e.address || ' ' || split_part(e.address, '@', 2) || ' ' || translate(e.address, '@.+', '   '), ' ')
+                FROM contact_emails e WHERE e.contact_id = c.id),
+            (SELECT string_agg(CONCAT_WS(' ', a.street, a.postal_code, a.locality, a.region, a.country), ' ')
+                FROM contact_addresses a WHERE a.contact_id = c.id),
+            (SELECT string_agg(t.name, ' ')
+                FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id WHERE ct.contact_id = c.id)
+        )
+        WHERE c.id = ANY($1)",
+        ids as &[ContactId]
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
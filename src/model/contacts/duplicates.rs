@@ -0,0 +1,121 @@
+use super::{Contact, Email, Phone};
+
+/// The score out of 100 from which two contacts are probably the same person.
+pub const LIKELY_DUPLICATE: u32 = 50;
+
+/// A pair of contacts that are probably the same person.
+pub struct Duplicate {
+    pub contact: Contact,
+    pub other: Contact,
+    pub score: u32,
+    pub reasons: Vec<&'static str>,
+}
+
+/// The parts of a contact that duplicates are recognized by, normalized.
+pub(super) struct Fingerprint {
+    /// The words of the name, lowercase and sorted, so that swapped first and last names match.
+    name: String,
+    /// The last nine digits of each phone number, which ignores country codes and trunk prefixes.
+    pub phones: Vec<String>,
+    /// The part of each email before the `@`, without `+suffix` and dots.
+    pub email_names: Vec<String>,
+}
+
+impl Fingerprint {
+    pub fn new(first: &str, last: &str, phones: &[Phone], emails: &[Email]) -> Self {
+        let mut words: Vec<String> = first
+            .split_whitespace()
+            .chain(last.split_whitespace())
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect()
+            })
+            .filter(|word: &String| !word.is_empty())
+            .collect();
+        words.sort();
+        let phones = phones
+            .iter()
+            .map(|phone| {
+                let digits: Vec<char> = phone.number.chars().filter(char::is_ascii_digit).collect();
+                digits[digits.len().saturating_sub(9)..].iter().collect()
+            })
+            .filter(|digits: &String| digits.len() >= 6)
+            .collect();
+        let email_names = emails
+            .iter()
+            .filter_map(|email| email.address.split_once('@'))
+            .map(|(name, _)| {
+                let name = name.split_once('+').map_or(name, |(name, _)| name);
+                name.replace('.', "").to_lowercase()
+            })
+            .filter(|name| !name.is_empty())
+            .collect();
+        Self {
+            name: words.join(" "),
+            phones,
+            email_names,
+        }
+    }
+
+    /// How likely `self` and `other` are the same person, out of 100, and why.
+    pub fn score(&self, other: &Fingerprint) -> (u32, Vec<&'static str>) {
+        let mut score = 0;
+        let mut reasons = Vec::new();
+        let similarity = similarity(&self.name, &other.name);
+        if similarity >= 0.8 {
+            score += (60.0 * similarity).round() as u32;
+            reasons.push(if similarity == 1.0 {
+                "Same name"
+            } else {
+                "Similar name"
+            });
+        }
+        if self.phones.iter().any(|phone| other.phones.contains(phone)) {
+            score += 30;
+            reasons.push("Same phone number");
+        }
+        if self
+            .email_names
+            .iter()
+            .any(|name| other.email_names.contains(name))
+        {
+            score += 20;
+            reasons.push("Same email name");
+        }
+        (score.min(100), reasons)
+    }
+}
+
+impl From<&Contact> for Fingerprint {
+    fn from(contact: &Contact) -> Self {
+        Self::new(
+            &contact.first,
+            &contact.last,
+            &contact.phones,
+            &contact.emails,
+        )
+    }
+}
+
+/// One minus the edit distance relative to the longer string, so 1.0 means equal.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
@@ -0,0 +1,414 @@
+use std::str::FromStr;
+
+use sqlx::{Postgres, QueryBuilder};
+
+/// A search for contacts, like `ada "analytical engine" -tag:old email:@example.com has:phone`.
+///
+/// Free words and phrases go to the full text index, while `field:value` filters, optionally
+/// negated with `-`, are compiled into conditions of their own.
+#[derive(Default, Debug)]
+pub(super) struct SearchQuery {
+    /// The free words and phrases, as a web search for the full text index.
+    pub(super) text: String,
+    /// The last free word while it is still being typed, which is matched as a prefix.
+    pub(super) prefix: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug)]
+struct Filter {
+    negated: bool,
+    condition: Condition,
+}
+
+#[derive(Debug)]
+enum Condition {
+    /// The field contains the value, ignoring case and accents.
+    Matches(Field, String),
+    Has(Detail),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Field {
+    First,
+    Last,
+    Email,
+    Phone,
+    Tag,
+    Company,
+}
+
+/// What a contact can have or miss.
+#[derive(Clone, Copy, Debug)]
+enum Detail {
+    Phone,
+    Email,
+    Address,
+    Tag,
+    Photo,
+    Birthday,
+    Company,
+    Notes,
+}
+
+impl SearchQuery {
+    /// Whether there is anything to search for in the full text index.
+    pub(super) fn has_text(&self) -> bool {
+        !self.text.trim().is_empty() || self.prefix.is_some()
+    }
+
+    /// Appends the filters as conditions on the `Contacts` table, each starting with `AND`.
+    pub(super) fn push_filters(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        for filter in &self.filters {
+            builder.push(if filter.negated { " AND NOT " } else { " AND " });
+            match &filter.condition {
+                Condition::Matches(Field::First, value) => {
+                    builder.push("search_fold(first) LIKE search_fold(");
+                    builder.push_bind(contains(value));
+                    builder.push(")");
+                }
+                Condition::Matches(Field::Last, value) => {
+                    builder.push("search_fold(last) LIKE search_fold(");
+                    builder.push_bind(contains(value));
+                    builder.push(")");
+                }
+                Condition::Matches(Field::Email, value) => {
+                    builder.push(
+                        "EXISTS (SELECT 1 FROM contact_emails e WHERE e.contact_id = Contacts.id
+                            AND search_fold(e.address) LIKE search_fold(",
+                    );
+                    builder.push_bind(contains(value));
+                    builder.push("))");
+                }
+                Condition::Matches(Field::Phone, value) => {
+                    builder.push(
+                        "EXISTS (SELECT 1 FROM contact_phones p WHERE p.contact_id = Contacts.id
+                            AND regexp_replace(p.number, '[^0-9+]', '', 'g') LIKE ",
+                    );
+                    builder.push_bind(contains(&phone_digits(value)));
+                    builder.push(")");
+                }
+                Condition::Matches(Field::Tag, value) => {
+                    builder.push(
+                        "EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+                            WHERE ct.contact_id = Contacts.id AND t.name = ",
+                    );
+                    builder.push_bind(value.to_lowercase());
+                    builder.push(")");
+                }
+                Condition::Matches(Field::Company, value) => {
+                    builder.push(
+                        "EXISTS (SELECT 1 FROM organizations o WHERE o.id = Contacts.organization_id
+                            AND search_fold(o.name) LIKE search_fold(",
+                    );
+                    builder.push_bind(contains(value));
+                    builder.push("))");
+                }
+                Condition::Has(detail) => {
+                    builder.push(match detail {
+                        Detail::Phone => {
+                            "EXISTS (SELECT 1 FROM contact_phones p WHERE p.contact_id = Contacts.id)"
+                        }
+                        Detail::Email => {
+                            "EXISTS (SELECT 1 FROM contact_emails e WHERE e.contact_id = Contacts.id)"
+                        }
+                        Detail::Address => {
+                            "EXISTS (SELECT 1 FROM contact_addresses a WHERE a.contact_id = Contacts.id)"
+                        }
+                        Detail::Tag => {
+                            "EXISTS (SELECT 1 FROM contact_tags ct WHERE ct.contact_id = Contacts.id)"
+                        }
+                        Detail::Photo => "has_photo",
+                        Detail::Birthday => "birthday IS NOT NULL",
+                        Detail::Company => "organization_id IS NOT NULL",
+                        Detail::Notes => "notes <> ''",
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut query = SearchQuery::default();
+        let mut text: Vec<String> = Vec::new();
+        // The last token, if it is a plain word that could still be being typed.
+        let mut last_word = None;
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i].is_whitespace() {
+                i += 1;
+                continue;
+            }
+            last_word = None;
+            let negated = chars[i] == '-' && chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+            if negated {
+                i += 1;
+            }
+            let sign = if negated { "-" } else { "" };
+
+            if chars[i] == '"' {
+                let (phrase, end) = quoted(&chars, i)?;
+                i = end;
+                if !phrase.trim().is_empty() {
+                    text.push(format!("{sign}\"{}\"", phrase.trim()));
+                }
+                continue;
+            }
+
+            let start = i;
+            while i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            if chars.get(i) == Some(&':') && i > start {
+                let name: String = chars[start..i].iter().collect();
+                i += 1;
+                let value = if chars.get(i) == Some(&'"') {
+                    let (value, end) = quoted(&chars, i)?;
+                    i = end;
+                    value
+                } else {
+                    let value_start = i;
+                    while i < chars.len() && !chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                    chars[value_start..i].iter().collect()
+                };
+                let (condition, missing) = Condition::parse(&name, value.trim())?;
+                query.filters.push(Filter {
+                    negated: negated != missing,
+                    condition,
+                });
+                continue;
+            }
+
+            while i < chars.len() && !chars[i].is_whitespace() {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().filter(|&&c| c != '"').collect();
+            if word.is_empty() {
+                continue;
+            }
+            if !negated {
+                last_word = Some(text.len());
+            }
+            text.push(format!("{sign}{word}"));
+        }
+
+        // Match the last word as a prefix, unless it is already complete or an operator.
+        if let Some(index) = last_word {
+            let word = &text[index];
+            if !input.ends_with(char::is_whitespace)
+                && word.chars().all(char::is_alphanumeric)
+                && !word.eq_ignore_ascii_case("or")
+            {
+                query.prefix = Some(text.remove(index));
+            }
+        }
+        query.text = text.join(" ");
+        Ok(query)
+    }
+}
+
+impl Condition {
+    /// The condition of filter `name:value`, and whether it is negated by being a `missing:`.
+    fn parse(name: &str, value: &str) -> Result<(Self, bool), String> {
+        let name = name.to_lowercase();
+        let field = match name.as_str() {
+            "has" | "missing" => {
+                let detail = match value.to_lowercase().as_str() {
+                    "phone" => Detail::Phone,
+                    "email" => Detail::Email,
+                    "address" => Detail::Address,
+                    "tag" => Detail::Tag,
+                    "photo" => Detail::Photo,
+                    "birthday" => Detail::Birthday,
+                    "company" => Detail::Company,
+                    "notes" => Detail::Notes,
+                    _ => {
+                        return Err(format!(
+                            "{name}: takes phone, email, address, tag, photo, birthday, company \
+                                or notes"
+                        ))
+                    }
+                };
+                return Ok((Condition::Has(detail), name == "missing"));
+            }
+            "first" => Field::First,
+            "last" => Field::Last,
+            "email" => Field::Email,
+            "phone" => Field::Phone,
+            "tag" => Field::Tag,
+            "company" => Field::Company,
+            _ => {
+                return Err(format!(
+                    "Unknown filter {name}:, use first:, last:, email:, phone:, tag:, company:, \
+                        has: or missing:"
+                ))
+            }
+        };
+        if value.is_empty() {
+            return Err(format!("{name}: needs a value"));
+        }
+        if matches!(field, Field::Phone) && !value.chars().any(|c| c.is_ascii_digit()) {
+            return Err(String::from("phone: needs digits"));
+        }
+        Ok((Condition::Matches(field, value.to_owned()), false))
+    }
+}
+
+/// The text between the quote at `start` and the closing one, and the index after that.
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let Some(length) = chars[start + 1..].iter().position(|&c| c == '"') else {
+        return Err(String::from("A quote is missing its closing quote"));
+    };
+    let end = start + 1 + length;
+    Ok((chars[start + 1..end].iter().collect(), end + 1))
+}
+
+/// A `LIKE` pattern that matches anything containing `value`.
+fn contains(value: &str) -> String {
+    format!("%{}%", escape_like(value))
+}
+
+/// A `LIKE` pattern that matches anything starting with `value`.
+pub(super) fn starts_with(value: &str) -> String {
+    format!("{}%", escape_like(value))
+}
+
+/// `value` with the wildcards of `LIKE` escaped, so that it only matches itself.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// The digits of a phone number and its leading `+`, the way they are compared.
+fn phone_digits(value: &str) -> String {
+    value
+        .chars()
+        .filter(|&c| c.is_ascii_digit() || c == '+')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> SearchQuery {
+        input.parse().unwrap()
+    }
+
+    fn sql(query: &SearchQuery) -> String {
+        let mut builder = QueryBuilder::new("");
+        query.push_filters(&mut builder);
+        builder.sql().to_owned()
+    }
+
+    #[test]
+    fn splits_words_into_text_and_prefix() {
+        let query = parse("ada love");
+        assert_eq!(query.text, "ada");
+        assert_eq!(query.prefix.as_deref(), Some("love"));
+        assert!(query.filters.is_empty());
+
+        let query = parse("ada lovelace ");
+        assert_eq!(query.text, "ada lovelace");
+        assert_eq!(query.prefix, None);
+    }
+
+    #[test]
+    fn keeps_phrases_and_negated_words() {
+        let query = parse(r#"ada "analytical engine" -babbage"#);
+        assert_eq!(query.text, r#"ada "analytical engine" -babbage"#);
+        assert_eq!(query.prefix, None);
+
+        let query = parse(r#"-"difference engine" ada"#);
+        assert_eq!(query.text, r#"-"difference engine""#);
+        assert_eq!(query.prefix.as_deref(), Some("ada"));
+    }
+
+    #[test]
+    fn rejects_unclosed_quotes() {
+        assert!(r#"ada "analytical"#.parse::<SearchQuery>().is_err());
+        assert!(r#"company:"Analytical"#.parse::<SearchQuery>().is_err());
+    }
+
+    #[test]
+    fn parses_field_filters() {
+        let query = parse(r#"first:Ada company:"Analytical Engines" phone:+44 20 ada"#);
+        assert_eq!(query.text, "20");
+        assert_eq!(query.prefix.as_deref(), Some("ada"));
+        assert!(matches!(
+            &query.filters[..],
+            [
+                Filter { negated: false, condition: Condition::Matches(Field::First, first) },
+                Filter { negated: false, condition: Condition::Matches(Field::Company, company) },
+                Filter { negated: false, condition: Condition::Matches(Field::Phone, phone) },
+            ] if first == "Ada" && company == "Analytical Engines" && phone == "+44"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        assert!("nickname:ada".parse::<SearchQuery>().is_err());
+        assert!("first:".parse::<SearchQuery>().is_err());
+        assert!("phone:home".parse::<SearchQuery>().is_err());
+        assert!("has:fax".parse::<SearchQuery>().is_err());
+    }
+
+    #[test]
+    fn negates_filters() {
+        let query = parse("-tag:old email:@example.com");
+        assert!(matches!(
+            &query.filters[..],
+            [
+                Filter { negated: true, condition: Condition::Matches(Field::Tag, tag) },
+                Filter { negated: false, condition: Condition::Matches(Field::Email, email) },
+            ] if tag == "old" && email == "@example.com"
+        ));
+        let sql = sql(&query);
+        assert!(sql.starts_with(" AND NOT EXISTS (SELECT 1 FROM contact_tags"));
+        assert!(sql.contains(" AND EXISTS (SELECT 1 FROM contact_emails"));
+    }
+
+    #[test]
+    fn parses_has_and_missing() {
+        let query = parse("has:Phone missing:photo -missing:birthday");
+        assert!(matches!(
+            &query.filters[..],
+            [
+                Filter {
+                    negated: false,
+                    condition: Condition::Has(Detail::Phone)
+                },
+                Filter {
+                    negated: true,
+                    condition: Condition::Has(Detail::Photo)
+                },
+                Filter {
+                    negated: false,
+                    condition: Condition::Has(Detail::Birthday)
+                },
+            ]
+        ));
+        assert_eq!(
+            sql(&query),
+            " AND EXISTS (SELECT 1 FROM contact_phones p WHERE p.contact_id = Contacts.id) \
+                AND NOT has_photo AND birthday IS NOT NULL"
+        );
+    }
+
+    #[test]
+    fn escapes_like_patterns() {
+        assert_eq!(contains("ada"), "%ada%");
+        assert_eq!(contains(r"50%_off\"), r"%50\%\_off\\%");
+        assert_eq!(starts_with("a_b%"), r"a\_b\%%");
+        assert_eq!(phone_digits("+44 (20) 7946-0958"), "+442079460958");
+    }
+}
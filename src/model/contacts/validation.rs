@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use super::{parse_date, ContactCandidate, Occasion};
+
+/// The most characters a name part may have.
+const MAX_NAME: usize = 100;
+/// The most characters of a label, a tag, an address part or a job title, department or company.
+const MAX_TEXT: usize = 200;
+/// The most characters of an email address, as allowed by SMTP.
+const MAX_EMAIL: usize = 254;
+const MAX_NOTES: usize = 10_000;
+/// The most digits of a phone number without the extension, as allowed by E.164.
+const MAX_PHONE_DIGITS: usize = 15;
+const MIN_PHONE_DIGITS: usize = 3;
+
+impl ContactCandidate {
+    /// Checks the values of the form, returning the errors keyed by the name of the form field.
+    /// Custom field values are checked separately against the schema of the address book.
+    pub fn validate(&self) -> Result<(), BTreeMap<String, String>> {
+        let mut errors = BTreeMap::new();
+        let mut error = |field: &str, message: String| {
+            errors.entry(field.to_owned()).or_insert(message);
+        };
+
+        if self.first.trim().is_empty() && self.last.trim().is_empty() {
+            error("first", String::from("Enter a first or last name"));
+        }
+        for (field, value, max) in [
+            ("first", &self.first, MAX_NAME),
+            ("last", &self.last, MAX_NAME),
+            ("prefix", &self.prefix, MAX_NAME),
+            ("middle", &self.middle, MAX_NAME),
+            ("suffix", &self.suffix, MAX_NAME),
+            ("nickname", &self.nickname, MAX_NAME),
+            ("phonetic_first", &self.phonetic_first, MAX_NAME),
+            ("phonetic_last", &self.phonetic_last, MAX_NAME),
+            ("organization", &self.organization, MAX_TEXT),
+            ("job_title", &self.job_title, MAX_TEXT),
+            ("department", &self.department, MAX_TEXT),
+            ("notes", &self.notes, MAX_NOTES),
+        ] {
+            if let Some(message) = too_long(value, max) {
+                error(field, message);
+            }
+        }
+
+        for phone in self.phones() {
+            if let Some(message) = too_long(&phone.label, MAX_TEXT) {
+                error("phone", message);
+            } else if !is_phone_number(&phone.number) {
+                error(
+                    "phone",
+                    format!(
+                        "\"{}\" is not a phone number like +49 30 1234567",
+                        phone.number
+                    ),
+                );
+            }
+        }
+        for email in self.emails() {
+            if let Some(message) = too_long(&email.label, MAX_TEXT) {
+                error("email", message);
+            } else if email.address.chars().count() > MAX_EMAIL || !is_email(&email.address) {
+                error(
+                    "email",
+                    format!(
+                        "\"{}\" is not an email like name@example.com",
+                        email.address
+                    ),
+                );
+            }
+        }
+        for address in self.addresses() {
+            for part in [
+                &address.label,
+                &address.street,
+                &address.locality,
+                &address.region,
+                &address.postal_code,
+                &address.country,
+            ] {
+                if let Some(message) = too_long(part, MAX_TEXT) {
+                    error("address", message);
+                }
+            }
+        }
+        for tag in self.tags() {
+            if let Some(message) = too_long(&tag, MAX_TEXT) {
+                error("tag", message);
+            }
+        }
+
+        if parse_date(&self.birthday, Occasion::Birthday).is_err() {
+            error(
+                "birthday",
+                String::from("Must be a date like 1990-12-31, or --12-31 without the year"),
+            );
+        }
+        if parse_date(&self.anniversary, Occasion::Anniversary).is_err() {
+            error(
+                "anniversary",
+                String::from("Must be a date like 1990-12-31, or --12-31 without the year"),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn too_long(value: &str, max: usize) -> Option<String> {
+    (value.trim().chars().count() > max).then(|| format!("Must be at most {max} characters"))
+}
+
+/// Whether `address` has a plausible local part and a domain with at least two labels.
+fn is_email(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !local.contains('@')
+        && !address.chars().any(|c| c.is_whitespace() || c.is_control())
+        && domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+/// Whether `number` is made of digits and the usual separators, with an optional leading `+`
+/// and an optional extension like "x123" or "ext. 123".
+// `Option::is_none_or` would need Rust 1.82.
+#[allow(clippy::unnecessary_map_or)]
+fn is_phone_number(number: &str) -> bool {
+    let lower = number.to_lowercase();
+    let (main, extension) = match lower.split_once("ext").or_else(|| lower.split_once('x')) {
+        Some((main, extension)) => (main, Some(extension.trim_start_matches('.').trim())),
+        None => (lower.as_str(), None),
+    };
+    let main = main.trim();
+    let main = main.strip_prefix('+').unwrap_or(main);
+    let digits = main.chars().filter(char::is_ascii_digit).count();
+    main.chars()
+        .all(|c| c.is_ascii_digit() || " ()-./".contains(c))
+        && (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits)
+        && extension.map_or(true, |extension| {
+            !extension.is_empty() && extension.chars().all(|c| c.is_ascii_digit())
+        })
+}
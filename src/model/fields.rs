@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::model::{BookId, Result};
+
+mod id;
+pub use id::FieldId;
+
+use super::Error;
+
+/// The values of a contact's custom fields, keyed by the field id.
+pub type CustomValues = serde_json::Map<String, Value>;
+
+#[derive(sqlx::Type, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "custom_field_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,
+    Boolean,
+    Select,
+}
+
+impl FieldKind {
+    pub const ALL: [FieldKind; 5] = [
+        FieldKind::Text,
+        FieldKind::Number,
+        FieldKind::Date,
+        FieldKind::Boolean,
+        FieldKind::Select,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Number => "number",
+            FieldKind::Date => "date",
+            FieldKind::Boolean => "boolean",
+            FieldKind::Select => "select",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CustomField {
+    pub id: FieldId,
+    pub name: String,
+    pub kind: FieldKind,
+    pub options: Vec<String>,
+}
+
+impl CustomField {
+    /// Turns the submitted form value into the stored JSON value.
+    /// `None` means the field was left empty.
+    fn parse(&self, raw: &str) -> Result<Option<Value>, String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Ok(None);
+        }
+        match self.kind {
+            FieldKind::Text => Ok(Some(Value::String(raw.to_owned()))),
+            FieldKind::Number => raw
+                .parse::<i64>()
+                .map(serde_json::Number::from)
+                .ok()
+                .or_else(|| raw.parse().ok().and_then(serde_json::Number::from_f64))
+                .map(|number| Some(Value::Number(number)))
+                .ok_or_else(|| String::from("Must be a number")),
+            FieldKind::Date => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map(|date| Some(Value::String(date.to_string())))
+                .map_err(|_| String::from("Must be a date like 2026-12-31")),
+            FieldKind::Boolean => match raw {
+                "true" => Ok(Some(Value::Bool(true))),
+                "false" => Ok(Some(Value::Bool(false))),
+                _ => Err(String::from("Must be yes or no")),
+            },
+            FieldKind::Select if self.options.iter().any(|option| option == raw) => {
+                Ok(Some(Value::String(raw.to_owned())))
+            }
+            FieldKind::Select => Err(String::from("Must be one of the options")),
+        }
+    }
+}
+
+/// Checks the submitted custom field values against the schema of the address book.
+/// Values for fields that don't exist (anymore) are dropped.
+pub fn parse_values(
+    fields: &[CustomField],
+    raw: &HashMap<String, String>,
+) -> Result<CustomValues, BTreeMap<String, String>> {
+    let mut values = CustomValues::new();
+    let mut errors = BTreeMap::new();
+    for field in fields {
+        let key = field.id.to_string();
+        let raw = raw.get(&key).map(String::as_str).unwrap_or_default();
+        match field.parse(raw) {
+            Ok(Some(value)) => {
+                values.insert(key, value);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                errors.insert(key, err);
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Renders a stored value the way it is entered into the form.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FieldCandidate {
+    pub name: String,
+    pub kind: FieldKind,
+    #[serde(default)]
+    pub options: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomFields {
+    db: PgPool,
+}
+
+impl CustomFields {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_all(&self, book: BookId) -> Result<Vec<CustomField>> {
+        let fields = sqlx::query_as!(
+            CustomField,
+            r#"SELECT id, name, kind as "kind: FieldKind", options FROM custom_fields
+                WHERE book_id = $1 ORDER BY position"#,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(fields)
+    }
+
+    /// Adds a field to `book`. Fields need a name, and select fields at least one option, as
+    /// they would reject every value otherwise.
+    pub async fn create(&self, book: BookId, field: &FieldCandidate) -> Result<FieldId> {
+        let options: Vec<String> = field
+            .options
+            .split(',')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .map(str::to_owned)
+            .collect();
+        if field.name.trim().is_empty() {
+            return Err(Error::MissingFieldName);
+        }
+        if field.kind == FieldKind::Select && options.is_empty() {
+            return Err(Error::MissingFieldOptions);
+        }
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO custom_fields (book_id, name, kind, options) VALUES ($1, $2, $3, $4)
+                RETURNING id as "id: FieldId""#,
+            book as BookId,
+            field.name.trim(),
+            field.kind as FieldKind,
+            &options
+        )
+        .fetch_one(&self.db)
+        .await;
+        match result {
+            Ok(id) => Ok(id),
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                Err(Error::DuplicateFieldName)
+            }
+            Err(err) => Err(err)?,
+        }
+    }
+
+    pub async fn delete_by_id(&self, book: BookId, id: FieldId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "DELETE FROM custom_fields WHERE id = $1 AND book_id = $2",
+            id as FieldId,
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE Contacts SET custom = custom - $1 WHERE book_id = $2",
+            id.to_string(),
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
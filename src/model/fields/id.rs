@@ -0,0 +1,50 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Clone, Copy, Debug)]
+#[serde(try_from = "FieldStringId", into = "FieldStringId")]
+#[sqlx(transparent)]
+pub struct FieldId(pub(super) Uuid);
+
+impl fmt::Display for FieldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Uuid> for FieldId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for FieldId {
+    type Err = <Uuid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::from_str(s)?))
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(transparent)]
+pub struct FieldStringId {
+    id: String,
+}
+
+impl From<FieldId> for FieldStringId {
+    fn from(value: FieldId) -> Self {
+        Self {
+            id: value.0.to_string(),
+        }
+    }
+}
+impl TryFrom<FieldStringId> for FieldId {
+    type Error = sqlx::types::uuid::Error;
+
+    fn try_from(value: FieldStringId) -> Result<Self, Self::Error> {
+        Ok(Self(value.id.parse()?))
+    }
+}
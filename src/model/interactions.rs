@@ -0,0 +1,132 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::model::{BookId, ContactId, Result};
+
+mod id;
+pub use id::InteractionId;
+
+use super::Error;
+
+#[derive(sqlx::Type, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "interaction_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum InteractionKind {
+    Call,
+    Meeting,
+    Email,
+    Note,
+}
+
+impl InteractionKind {
+    pub const ALL: [InteractionKind; 4] = [
+        InteractionKind::Call,
+        InteractionKind::Meeting,
+        InteractionKind::Email,
+        InteractionKind::Note,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InteractionKind::Call => "call",
+            InteractionKind::Meeting => "meeting",
+            InteractionKind::Email => "email",
+            InteractionKind::Note => "note",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InteractionKind::Call => "Call",
+            InteractionKind::Meeting => "Meeting",
+            InteractionKind::Email => "Email",
+            InteractionKind::Note => "Note",
+        }
+    }
+}
+
+/// A call, meeting, email or note logged against a contact.
+pub struct Interaction {
+    pub id: InteractionId,
+    pub kind: InteractionKind,
+    pub date: NaiveDate,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+pub struct InteractionCandidate {
+    pub kind: InteractionKind,
+    pub date: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Interactions {
+    db: PgPool,
+}
+
+impl Interactions {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// The interactions with contact `id`, latest first.
+    pub async fn get_for_contact(&self, book: BookId, id: ContactId) -> Result<Vec<Interaction>> {
+        let interactions = sqlx::query_as!(
+            Interaction,
+            r#"SELECT i.id, i.kind as "kind: InteractionKind", i.happened_on as date, i.text
+                FROM interactions i JOIN Contacts c ON c.id = i.contact_id
+                WHERE i.contact_id = $1 AND c.book_id = $2
+                ORDER BY i.happened_on DESC, i.created_at DESC"#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(interactions)
+    }
+
+    pub async fn create(
+        &self,
+        book: BookId,
+        id: ContactId,
+        candidate: &InteractionCandidate,
+    ) -> Result<InteractionId> {
+        let date = NaiveDate::parse_from_str(candidate.date.trim(), "%Y-%m-%d")
+            .map_err(|_| Error::InvalidInteraction)?;
+        let interaction = sqlx::query_scalar!(
+            r#"INSERT INTO interactions (contact_id, happened_on, kind, text)
+                SELECT id, $2, $3, $4 FROM Contacts
+                WHERE id = $1 AND book_id = $5 AND deleted_at IS NULL
+                RETURNING id as "id: InteractionId""#,
+            id as ContactId,
+            date,
+            candidate.kind as InteractionKind,
+            candidate.text.trim(),
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        interaction.ok_or(Error::InvalidInteraction)
+    }
+
+    pub async fn delete_by_id(
+        &self,
+        book: BookId,
+        id: ContactId,
+        interaction: InteractionId,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM interactions i USING Contacts c
+                WHERE i.id = $1 AND i.contact_id = $2 AND c.id = $2 AND c.book_id = $3",
+            interaction as InteractionId,
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
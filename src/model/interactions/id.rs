@@ -0,0 +1,50 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Clone, Copy, Debug)]
+#[serde(try_from = "InteractionStringId", into = "InteractionStringId")]
+#[sqlx(transparent)]
+pub struct InteractionId(pub(super) Uuid);
+
+impl fmt::Display for InteractionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Uuid> for InteractionId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for InteractionId {
+    type Err = <Uuid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::from_str(s)?))
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(transparent)]
+pub struct InteractionStringId {
+    id: String,
+}
+
+impl From<InteractionId> for InteractionStringId {
+    fn from(value: InteractionId) -> Self {
+        Self {
+            id: value.0.to_string(),
+        }
+    }
+}
+impl TryFrom<InteractionStringId> for InteractionId {
+    type Error = sqlx::types::uuid::Error;
+
+    fn try_from(value: InteractionStringId) -> Result<Self, Self::Error> {
+        Ok(Self(value.id.parse()?))
+    }
+}
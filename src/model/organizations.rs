@@ -0,0 +1,209 @@
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
+
+use crate::model::{contacts, BookId, ContactId, Result};
+
+mod id;
+pub use id::OrganizationId;
+
+use super::Error;
+
+#[derive(Clone, Debug)]
+pub struct Organization {
+    pub id: OrganizationId,
+    pub name: String,
+    pub website: String,
+    pub notes: String,
+}
+
+impl Organization {
+    /// The website as a link target, assuming https if no scheme was given. Websites with any
+    /// other scheme than http or https, like `javascript://`, aren't linked at all.
+    pub fn website_url(&self) -> Option<String> {
+        let website = self.website.trim();
+        if website.is_empty() {
+            return None;
+        }
+        match website.split_once("://") {
+            Some((scheme, _))
+                if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") =>
+            {
+                Some(website.to_owned())
+            }
+            Some((scheme, _))
+                if scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) =>
+            {
+                None
+            }
+            _ => Some(format!("https://{website}")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OrganizationCandidate {
+    pub name: String,
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Organizations {
+    db: PgPool,
+}
+
+impl Organizations {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_all(&self, book: BookId) -> Result<Vec<Organization>> {
+        let organizations = sqlx::query_as!(
+            Organization,
+            "SELECT id, name, website, notes FROM organizations WHERE book_id = $1 ORDER BY name",
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(organizations)
+    }
+
+    pub async fn get_by_id(
+        &self,
+        book: BookId,
+        id: OrganizationId,
+    ) -> Result<Option<Organization>> {
+        let organization = sqlx::query_as!(
+            Organization,
+            "SELECT id, name, website, notes FROM organizations WHERE id = $1 AND book_id = $2",
+            id as OrganizationId,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(organization)
+    }
+
+    pub async fn create(
+        &self,
+        book: BookId,
+        organization: &OrganizationCandidate,
+    ) -> Result<OrganizationId> {
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO organizations (book_id, name, website, notes) VALUES ($1, $2, $3, $4)
+                RETURNING id as "id: OrganizationId""#,
+            book as BookId,
+            organization.name.trim(),
+            organization.website.trim(),
+            organization.notes,
+        )
+        .fetch_one(&self.db)
+        .await;
+        result.map_err(unique_violation_to_duplicate_name)
+    }
+
+    pub async fn update_by_id(
+        &self,
+        book: BookId,
+        id: OrganizationId,
+        organization: &OrganizationCandidate,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "UPDATE organizations SET name = $1, website = $2, notes = $3
+                WHERE id = $4 AND book_id = $5",
+            organization.name.trim(),
+            organization.website.trim(),
+            organization.notes,
+            id as OrganizationId,
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(unique_violation_to_duplicate_name)?;
+        let members = members(&mut tx, id).await?;
+        contacts::refresh_search(&mut tx, &members).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the organization. Its members are kept, just without an organization.
+    pub async fn delete_by_id(&self, book: BookId, id: OrganizationId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        let members = members(&mut tx, id).await?;
+        sqlx::query!(
+            "DELETE FROM organizations WHERE id = $1 AND book_id = $2",
+            id as OrganizationId,
+            book as BookId
+        )
+        .execute(&mut *tx)
+        .await?;
+        contacts::refresh_search(&mut tx, &members).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The names of up to ten organizations in `book` starting with `prefix`.
+    pub async fn get_names_by_prefix(&self, book: BookId, prefix: &str) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar!(
+            "SELECT name FROM organizations WHERE book_id = $1 AND name ILIKE CONCAT($2::TEXT, '%')
+                ORDER BY name LIMIT 10",
+            book as BookId,
+            prefix
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(names)
+    }
+}
+
+/// The ids of all contacts that belong to the organization, including the ones in the trash.
+async fn members(conn: &mut PgConnection, id: OrganizationId) -> sqlx::Result<Vec<ContactId>> {
+    sqlx::query_scalar!(
+        r#"SELECT id as "id: ContactId" FROM Contacts WHERE organization_id = $1"#,
+        id as OrganizationId
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// The id of the organization called `name` in `book`, creating it if there is none yet.
+/// An empty name means no organization.
+pub(super) async fn find_or_create(
+    conn: &mut PgConnection,
+    book: BookId,
+    name: &str,
+) -> sqlx::Result<Option<OrganizationId>> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    sqlx::query!(
+        "INSERT INTO organizations (book_id, name) VALUES ($1, $2) ON CONFLICT (book_id, name) DO NOTHING",
+        book as BookId,
+        name
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar!(
+        r#"SELECT id as "id: OrganizationId" FROM organizations WHERE book_id = $1 AND name = $2"#,
+        book as BookId,
+        name
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map(Some)
+}
+
+fn unique_violation_to_duplicate_name(err: sqlx::Error) -> Error {
+    match err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            Error::DuplicateOrganizationName
+        }
+        err => err.into(),
+    }
+}
@@ -0,0 +1,50 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Clone, Copy, Debug)]
+#[serde(try_from = "OrganizationStringId", into = "OrganizationStringId")]
+#[sqlx(transparent)]
+pub struct OrganizationId(pub(super) Uuid);
+
+impl fmt::Display for OrganizationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Uuid> for OrganizationId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for OrganizationId {
+    type Err = <Uuid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::from_str(s)?))
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(transparent)]
+pub struct OrganizationStringId {
+    id: String,
+}
+
+impl From<OrganizationId> for OrganizationStringId {
+    fn from(value: OrganizationId) -> Self {
+        Self {
+            id: value.0.to_string(),
+        }
+    }
+}
+impl TryFrom<OrganizationStringId> for OrganizationId {
+    type Error = sqlx::types::uuid::Error;
+
+    fn try_from(value: OrganizationStringId) -> Result<Self, Self::Error> {
+        Ok(Self(value.id.parse()?))
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fmt, path::PathBuf};
+
+use image::{imageops::FilterType, DynamicImage, ImageError, ImageFormat};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::model::{BookId, ContactId, Error, Result};
+
+/// The fixed sizes thumbnails are generated in.
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PhotoSize {
+    Small,
+    Large,
+}
+
+impl PhotoSize {
+    const ALL: [PhotoSize; 2] = [PhotoSize::Small, PhotoSize::Large];
+
+    /// Width and height of the thumbnail in pixels.
+    pub fn pixels(&self) -> u32 {
+        match self {
+            PhotoSize::Small => 48,
+            PhotoSize::Large => 256,
+        }
+    }
+}
+
+impl fmt::Display for PhotoSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhotoSize::Small => f.write_str("small"),
+            PhotoSize::Large => f.write_str("large"),
+        }
+    }
+}
+
+/// Stores the thumbnails of contact photos as PNG files in `dir`.
+#[derive(Debug, Clone)]
+pub struct Photos {
+    db: PgPool,
+    dir: PathBuf,
+}
+
+impl Photos {
+    pub fn new(db: PgPool, dir: PathBuf) -> Self {
+        Self { db, dir }
+    }
+
+    pub fn path(&self, id: ContactId, size: PhotoSize) -> PathBuf {
+        self.dir.join(format!("{id}-{size}.png"))
+    }
+
+    /// The thumbnail of contact `id` in `size`, or `None` if there is no such contact with a
+    /// photo in `book`.
+    pub async fn file(
+        &self,
+        book: BookId,
+        id: ContactId,
+        size: PhotoSize,
+    ) -> Result<Option<PathBuf>> {
+        let has_photo = sqlx::query_scalar!(
+            "SELECT has_photo FROM Contacts WHERE id = $1 AND book_id = $2",
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok((has_photo == Some(true)).then(|| self.path(id, size)))
+    }
+
+    /// Replaces the photo of contact `id` with the image in `bytes`. The thumbnails are written
+    /// before the contact is marked as having a photo, so that it never points at missing files.
+    /// Returns `false` if there is no such contact in `book`.
+    pub async fn store(&self, book: BookId, id: ContactId, bytes: Vec<u8>) -> Result<bool> {
+        let image = tokio::task::spawn_blocking(move || decode(&bytes))
+            .await
+            .expect("decoding the photo panicked")?;
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM Contacts WHERE id = $1 AND book_id = $2 AND deleted_at IS NULL
+            ) as "exists!""#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_one(&self.db)
+        .await?;
+        if !exists {
+            return Ok(false);
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let paths = PhotoSize::ALL.map(|size| (size, self.path(id, size)));
+        tokio::task::spawn_blocking(move || {
+            for (size, path) in paths {
+                image
+                    .resize_to_fill(size.pixels(), size.pixels(), FilterType::Lanczos3)
+                    .save_with_format(path, ImageFormat::Png)
+                    .map_err(|err| match err {
+                        ImageError::IoError(err) => Error::Io(err),
+                        _ => Error::InvalidImage,
+                    })?;
+            }
+            Ok::<_, Error>(())
+        })
+        .await
+        .expect("writing the thumbnails panicked")?;
+
+        let updated = sqlx::query!(
+            "UPDATE Contacts SET has_photo = true WHERE id = $1 AND book_id = $2 AND deleted_at IS NULL",
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    pub async fn delete(&self, book: BookId, id: ContactId) -> Result<()> {
+        sqlx::query!(
+            "UPDATE Contacts SET has_photo = false WHERE id = $1 AND book_id = $2",
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        self.remove_files(id).await
+    }
+
+    /// Removes the thumbnails of contact `id`, if there are any.
+    pub async fn remove_files(&self, id: ContactId) -> Result<()> {
+        for size in PhotoSize::ALL {
+            match tokio::fs::remove_file(self.path(id, size)).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `bytes` really is an image in one of the supported formats.
+fn decode(bytes: &[u8]) -> Result<DynamicImage> {
+    let format = image::guess_format(bytes).map_err(|_| Error::InvalidImage)?;
+    image::load_from_memory_with_format(bytes, format).map_err(|_| Error::InvalidImage)
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::{error, info};
+
+use crate::model::{Contacts, Photos, Result};
+
+/// Deletes contacts for good once they have been in the trash for longer than the retention period.
+#[derive(Debug, Clone)]
+pub struct Purger {
+    contacts: Contacts,
+    photos: Photos,
+    retention: chrono::Duration,
+}
+
+impl Purger {
+    pub fn new(contacts: Contacts, photos: Photos, retention_days: u32) -> Self {
+        Self {
+            contacts,
+            photos,
+            retention: chrono::Duration::days(retention_days.into()),
+        }
+    }
+
+    pub fn retention_days(&self) -> i64 {
+        self.retention.num_days()
+    }
+
+    /// Purges the trash once now and then every hour in the background.
+    pub fn spawn(&self) {
+        let purger = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+                if let Err(err) = purger.purge().await {
+                    error!(%err, "purging the trash failed");
+                }
+            }
+        });
+    }
+
+    async fn purge(&self) -> Result<()> {
+        let ids = self
+            .contacts
+            .purge_deleted_before(Utc::now() - self.retention)
+            .await?;
+        for &id in &ids {
+            self.photos.remove_files(id).await?;
+        }
+        if !ids.is_empty() {
+            info!(count = ids.len(), "purged contacts from the trash");
+        }
+        Ok(())
+    }
+}
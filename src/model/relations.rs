@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::model::{BookId, ContactId, NameFormat, Result};
+
+mod id;
+pub use id::RelationId;
+
+use super::Error;
+
+#[derive(sqlx::Type, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "relation_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Spouse,
+    Assistant,
+    ReportsTo,
+    ReferredBy,
+}
+
+impl RelationKind {
+    pub const ALL: [RelationKind; 4] = [
+        RelationKind::Spouse,
+        RelationKind::Assistant,
+        RelationKind::ReportsTo,
+        RelationKind::ReferredBy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RelationKind::Spouse => "spouse",
+            RelationKind::Assistant => "assistant",
+            RelationKind::ReportsTo => "reports_to",
+            RelationKind::ReferredBy => "referred_by",
+        }
+    }
+
+    /// How the relation reads from the contact that has it.
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelationKind::Spouse => "Spouse of",
+            RelationKind::Assistant => "Assistant of",
+            RelationKind::ReportsTo => "Reports to",
+            RelationKind::ReferredBy => "Referred by",
+        }
+    }
+
+    /// How the relation reads from the other side.
+    pub fn inverse_label(&self) -> &'static str {
+        match self {
+            RelationKind::Spouse => "Spouse of",
+            RelationKind::Assistant => "Has assistant",
+            RelationKind::ReportsTo => "Manager of",
+            RelationKind::ReferredBy => "Referred",
+        }
+    }
+}
+
+/// A relation as seen from one of the two contacts.
+pub struct Relation {
+    pub id: RelationId,
+    pub kind: RelationKind,
+    /// Whether the relation was recorded on the other contact, e.g. "Manager of" for "reports to".
+    pub inverse: bool,
+    pub other: ContactId,
+    /// The full name of the other contact in the format of the address book.
+    pub name: String,
+}
+
+impl Relation {
+    pub fn label(&self) -> &'static str {
+        if self.inverse {
+            self.kind.inverse_label()
+        } else {
+            self.kind.label()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RelationCandidate {
+    pub kind: RelationKind,
+    pub related: ContactId,
+}
+
+#[derive(Debug, Clone)]
+pub struct Relations {
+    db: PgPool,
+}
+
+impl Relations {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// The relations of contact `id` in both directions, by the name of the other contact.
+    pub async fn get_for_contact(&self, book: BookId, id: ContactId) -> Result<Vec<Relation>> {
+        let relations = sqlx::query!(
+            r#"SELECT r.id as "id!: RelationId", r.kind as "kind!: RelationKind",
+                    r.inverse as "inverse!", c.id as "other: ContactId", c.prefix, c.first,
+                    c.middle, c.last, c.suffix, b.name_format as "name_format: NameFormat"
+                FROM (
+                    SELECT id, kind, false as inverse, related_id as other_id
+                        FROM contact_relations WHERE contact_id = $1
+                    UNION ALL
+                    SELECT id, kind, true as inverse, contact_id as other_id
+                        FROM contact_relations WHERE related_id = $1
+                ) r
+                JOIN Contacts c ON c.id = r.other_id
+                JOIN address_books b ON b.id = c.book_id
+                WHERE c.book_id = $2 AND c.deleted_at IS NULL
+                ORDER BY CASE WHEN b.name_format = 'last_first' THEN c.sort_last ELSE c.sort_first END,
+                    CASE WHEN b.name_format = 'last_first' THEN c.sort_first ELSE c.sort_last END,
+                    c.id"#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(relations
+            .into_iter()
+            .map(|row| Relation {
+                id: row.id,
+                kind: row.kind,
+                inverse: row.inverse,
+                other: row.other,
+                name: row.name_format.full_name(
+                    &row.prefix,
+                    &row.first,
+                    &row.middle,
+                    &row.last,
+                    &row.suffix,
+                ),
+            })
+            .collect())
+    }
+
+    /// Records that contact `id` is `candidate.kind` of `candidate.related`.
+    /// Both contacts have to be in `book`, and spouses can't be recorded from both sides.
+    pub async fn create(
+        &self,
+        book: BookId,
+        id: ContactId,
+        candidate: &RelationCandidate,
+    ) -> Result<RelationId> {
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO contact_relations (contact_id, related_id, kind)
+                SELECT c.id, r.id, $3 FROM Contacts c, Contacts r
+                WHERE c.id = $1 AND r.id = $2 AND c.book_id = $4 AND r.book_id = $4
+                  AND c.deleted_at IS NULL AND r.deleted_at IS NULL
+                RETURNING id as "id: RelationId""#,
+            id as ContactId,
+            candidate.related as ContactId,
+            candidate.kind as RelationKind,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await;
+        match result {
+            Ok(Some(relation)) => Ok(relation),
+            Ok(None) => Err(Error::InvalidRelation),
+            Err(sqlx::Error::Database(db_err))
+                if db_err.is_unique_violation() || db_err.is_check_violation() =>
+            {
+                Err(Error::InvalidRelation)
+            }
+            Err(err) => Err(err)?,
+        }
+    }
+
+    /// Removes a relation of contact `id`, no matter in which direction it was recorded.
+    pub async fn delete_by_id(
+        &self,
+        book: BookId,
+        id: ContactId,
+        relation: RelationId,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM contact_relations r USING Contacts c
+                WHERE r.id = $1 AND (r.contact_id = $2 OR r.related_id = $2)
+                  AND c.id = $2 AND c.book_id = $3",
+            relation as RelationId,
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
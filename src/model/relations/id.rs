@@ -0,0 +1,50 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Clone, Copy, Debug)]
+#[serde(try_from = "RelationStringId", into = "RelationStringId")]
+#[sqlx(transparent)]
+pub struct RelationId(pub(super) Uuid);
+
+impl fmt::Display for RelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Uuid> for RelationId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for RelationId {
+    type Err = <Uuid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::from_str(s)?))
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(transparent)]
+pub struct RelationStringId {
+    id: String,
+}
+
+impl From<RelationId> for RelationStringId {
+    fn from(value: RelationId) -> Self {
+        Self {
+            id: value.0.to_string(),
+        }
+    }
+}
+impl TryFrom<RelationStringId> for RelationId {
+    type Error = sqlx::types::uuid::Error;
+
+    fn try_from(value: RelationStringId) -> Result<Self, Self::Error> {
+        Ok(Self(value.id.parse()?))
+    }
+}
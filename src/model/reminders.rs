@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::model::{BookId, ContactId, NameFormat, Result};
+
+mod id;
+pub use id::ReminderId;
+
+use super::Error;
+
+/// Something to follow up on with a contact by a due date.
+pub struct Reminder {
+    pub id: ReminderId,
+    pub book: BookId,
+    pub contact: ContactId,
+    /// The full name of the contact in the format of the address book.
+    pub name: String,
+    pub due: NaiveDate,
+    pub text: String,
+    pub done: bool,
+}
+
+/// A reminder with the name parts of its contact, before they are put together.
+struct ReminderRow {
+    id: ReminderId,
+    book: BookId,
+    contact: ContactId,
+    prefix: String,
+    first: String,
+    middle: String,
+    last: String,
+    suffix: String,
+    name_format: NameFormat,
+    due: NaiveDate,
+    text: String,
+    done: bool,
+}
+
+impl From<ReminderRow> for Reminder {
+    fn from(row: ReminderRow) -> Self {
+        Reminder {
+            id: row.id,
+            book: row.book,
+            contact: row.contact,
+            name: row.name_format.full_name(
+                &row.prefix,
+                &row.first,
+                &row.middle,
+                &row.last,
+                &row.suffix,
+            ),
+            due: row.due,
+            text: row.text,
+            done: row.done,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReminderCandidate {
+    pub due: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reminders {
+    db: PgPool,
+}
+
+impl Reminders {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// The reminders of contact `id`, open ones first, by due date.
+    pub async fn get_for_contact(&self, book: BookId, id: ContactId) -> Result<Vec<Reminder>> {
+        let reminders = sqlx::query_as!(
+            ReminderRow,
+            r#"SELECT r.id, c.book_id as book, c.id as contact, c.prefix, c.first, c.middle, c.last,
+                    c.suffix, b.name_format as "name_format: NameFormat", r.due_on as due,
+                    r.text, r.done
+                FROM reminders r JOIN Contacts c ON c.id = r.contact_id
+                    JOIN address_books b ON b.id = c.book_id
+                WHERE r.contact_id = $1 AND c.book_id = $2
+                ORDER BY r.done, r.due_on, r.created_at"#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(reminders.into_iter().map(Reminder::from).collect())
+    }
+
+    /// The reminders of all address books that aren't done yet, by due date.
+    pub async fn get_open(&self) -> Result<Vec<Reminder>> {
+        let reminders = sqlx::query_as!(
+            ReminderRow,
+            r#"SELECT r.id, c.book_id as book, c.id as contact, c.prefix, c.first, c.middle, c.last,
+                    c.suffix, b.name_format as "name_format: NameFormat", r.due_on as due,
+                    r.text, r.done
+                FROM reminders r JOIN Contacts c ON c.id = r.contact_id
+                    JOIN address_books b ON b.id = c.book_id
+                WHERE NOT r.done AND c.deleted_at IS NULL
+                ORDER BY r.due_on, r.created_at"#
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(reminders.into_iter().map(Reminder::from).collect())
+    }
+
+    /// How many open reminders were due before `today`.
+    pub async fn count_overdue(&self, today: NaiveDate) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM reminders r JOIN Contacts c ON c.id = r.contact_id
+                WHERE NOT r.done AND r.due_on < $1 AND c.deleted_at IS NULL"#,
+            today
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count)
+    }
+
+    pub async fn create(
+        &self,
+        book: BookId,
+        id: ContactId,
+        candidate: &ReminderCandidate,
+    ) -> Result<ReminderId> {
+        let due = NaiveDate::parse_from_str(candidate.due.trim(), "%Y-%m-%d")
+            .map_err(|_| Error::InvalidReminder)?;
+        let text = candidate.text.trim();
+        if text.is_empty() {
+            return Err(Error::InvalidReminder);
+        }
+        let reminder = sqlx::query_scalar!(
+            r#"INSERT INTO reminders (contact_id, due_on, text)
+                SELECT id, $2, $3 FROM Contacts
+                WHERE id = $1 AND book_id = $4 AND deleted_at IS NULL
+                RETURNING id as "id: ReminderId""#,
+            id as ContactId,
+            due,
+            text,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        reminder.ok_or(Error::InvalidReminder)
+    }
+
+    pub async fn set_done(
+        &self,
+        book: BookId,
+        id: ContactId,
+        reminder: ReminderId,
+        done: bool,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE reminders r SET done = $1 FROM Contacts c
+                WHERE r.id = $2 AND r.contact_id = $3 AND c.id = $3 AND c.book_id = $4",
+            done,
+            reminder as ReminderId,
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_by_id(
+        &self,
+        book: BookId,
+        id: ContactId,
+        reminder: ReminderId,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM reminders r USING Contacts c
+                WHERE r.id = $1 AND r.contact_id = $2 AND c.id = $2 AND c.book_id = $3",
+            reminder as ReminderId,
+            id as ContactId,
+            book as BookId
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, PgConnection, PgPool};
+
+use crate::model::{BookId, Change, ContactCandidate, ContactId, CustomField, Result};
+
+mod id;
+pub use id::RevisionId;
+
+#[derive(sqlx::Type, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "revision_action", rename_all = "lowercase")]
+pub enum RevisionAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Merge,
+}
+
+/// The state of a contact right after it was created, updated, deleted or restored.
+pub struct Revision {
+    pub id: RevisionId,
+    pub action: RevisionAction,
+    pub created_at: DateTime<Utc>,
+    pub snapshot: ContactCandidate,
+}
+
+struct RevisionRow {
+    id: RevisionId,
+    action: RevisionAction,
+    created_at: DateTime<Utc>,
+    snapshot: Json<ContactCandidate>,
+}
+
+impl From<RevisionRow> for Revision {
+    fn from(row: RevisionRow) -> Self {
+        Self {
+            id: row.id,
+            action: row.action,
+            created_at: row.created_at,
+            snapshot: row.snapshot.0,
+        }
+    }
+}
+
+impl Revision {
+    /// The fields of this revision that differ from `previous`, or all non-empty fields if there
+    /// is no previous revision. Custom fields are named after `fields`.
+    pub fn changes(&self, previous: Option<&Revision>, fields: &[CustomField]) -> Vec<Change> {
+        self.snapshot
+            .changes(previous.map(|previous| &previous.snapshot), fields)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Revisions {
+    db: PgPool,
+}
+
+impl Revisions {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// All revisions of contact `id`, newest first.
+    pub async fn get_for_contact(&self, book: BookId, id: ContactId) -> Result<Vec<Revision>> {
+        let rows = sqlx::query_as!(
+            RevisionRow,
+            r#"SELECT r.id, r.action as "action: RevisionAction", r.created_at,
+                    r.snapshot as "snapshot: Json<ContactCandidate>"
+                FROM contact_revisions r JOIN Contacts c ON c.id = r.contact_id
+                WHERE r.contact_id = $1 AND c.book_id = $2
+                ORDER BY r.created_at DESC"#,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Revision::from).collect())
+    }
+
+    pub async fn get_by_id(
+        &self,
+        book: BookId,
+        id: ContactId,
+        revision: RevisionId,
+    ) -> Result<Option<Revision>> {
+        let row = sqlx::query_as!(
+            RevisionRow,
+            r#"SELECT r.id, r.action as "action: RevisionAction", r.created_at,
+                    r.snapshot as "snapshot: Json<ContactCandidate>"
+                FROM contact_revisions r JOIN Contacts c ON c.id = r.contact_id
+                WHERE r.id = $1 AND r.contact_id = $2 AND c.book_id = $3"#,
+            revision as RevisionId,
+            id as ContactId,
+            book as BookId
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(Revision::from))
+    }
+
+    /// The newest revision of contact `id` that is the state of the contact at `version`.
+    pub async fn get_by_version(
+        &self,
+        book: BookId,
+        id: ContactId,
+        version: i32,
+    ) -> Result<Option<Revision>> {
+        let row = sqlx::query_as!(
+            RevisionRow,
+            r#"SELECT r.id, r.action as "action: RevisionAction", r.created_at,
+                    r.snapshot as "snapshot: Json<ContactCandidate>"
+                FROM contact_revisions r JOIN Contacts c ON c.id = r.contact_id
+                WHERE r.contact_id = $1 AND c.book_id = $2 AND r.version = $3
+                ORDER BY r.created_at DESC
+                LIMIT 1"#,
+            id as ContactId,
+            book as BookId,
+            version
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(Revision::from))
+    }
+}
+
+/// Adds a revision with `snapshot` to the history of contact `id`, as the state of its current
+/// version.
+pub(super) async fn record(
+    conn: &mut PgConnection,
+    id: ContactId,
+    action: RevisionAction,
+    snapshot: &ContactCandidate,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO contact_revisions (contact_id, action, snapshot, version)
+            SELECT id, $2, $3, version FROM Contacts WHERE id = $1",
+        id as ContactId,
+        action as RevisionAction,
+        Json(snapshot) as _
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
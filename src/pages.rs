@@ -1,12 +1,14 @@
 use axum::response::Redirect;
 use axum_extra::routing::TypedPath;
 
+pub mod books;
 pub mod contacts;
+pub mod reminders;
 
 #[derive(TypedPath)]
 #[typed_path("/")]
 pub struct Path;
 
 pub async fn get(_: Path) -> Redirect {
-    Redirect::to(&contacts::Path.to_string())
+    Redirect::to(&books::Path.to_string())
 }
@@ -0,0 +1,81 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::{Flash, IncomingFlashes};
+use serde::Deserialize;
+
+use crate::{
+    model,
+    pages::contacts::{self, shared},
+    Result,
+};
+
+pub mod fields;
+pub mod name_format;
+pub mod organizations;
+
+#[derive(TypedPath)]
+#[typed_path("/books")]
+pub struct Path;
+
+#[derive(Template)]
+#[template(path = "books.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub books: Vec<model::AddressBook>,
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewBook {
+    name: String,
+}
+
+pub async fn get(
+    _: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+) -> Result<Response> {
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            books: books.get_all().await?,
+            name: String::new(),
+            error: String::new(),
+        },
+    )
+        .into_response())
+}
+
+pub async fn post(
+    _: Path,
+    State(books): State<model::AddressBooks>,
+    flash: Flash,
+    Form(NewBook { name }): Form<NewBook>,
+) -> Result<Response> {
+    match books.create(&name).await {
+        Ok(book) => Ok((
+            flash.success("Address book created"),
+            Redirect::to(&contacts::Path { book }.to_string()),
+        )
+            .into_response()),
+        Err(model::Error::DuplicateBookName) => Ok((
+            flash.error("Address book could not be created"),
+            Tmpl {
+                layout: shared::Layout { flashes: None },
+                books: books.get_all().await?,
+                name,
+                error: String::from("Address book already exists"),
+            },
+        )
+            .into_response()),
+        Err(err) => Err(err)?,
+    }
+}
@@ -0,0 +1,110 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::{Flash, IncomingFlashes};
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, FieldKind},
+    pages::contacts::{self, shared},
+    Result,
+};
+
+pub mod item;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/fields")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "fields.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub fields: Vec<model::CustomField>,
+    pub name: String,
+    pub options: String,
+    pub error: String,
+    pub options_error: String,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+    State(fields): State<model::CustomFields>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            book_name: address_book.name,
+            fields: fields.get_all(book).await?,
+            name: String::new(),
+            options: String::new(),
+            error: String::new(),
+            options_error: String::new(),
+        },
+    )
+        .into_response())
+}
+
+pub async fn post(
+    Path { book }: Path,
+    State(books): State<model::AddressBooks>,
+    State(fields): State<model::CustomFields>,
+    flash: Flash,
+    Form(field): Form<model::FieldCandidate>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    match fields.create(book, &field).await {
+        Ok(_) => Ok((
+            flash.success("Field created"),
+            Redirect::to(&Path { book }.to_string()),
+        )
+            .into_response()),
+        Err(err) => {
+            let (error, options_error) = match err {
+                model::Error::DuplicateFieldName => ("Field already exists", ""),
+                model::Error::MissingFieldName => ("Field needs a name", ""),
+                model::Error::MissingFieldOptions => ("", "Select fields need at least one option"),
+                err => return Err(err)?,
+            };
+            Ok((
+                flash.error("Field could not be created"),
+                Tmpl {
+                    layout: shared::Layout { flashes: None },
+                    book,
+                    book_name: address_book.name,
+                    fields: fields.get_all(book).await?,
+                    name: field.name,
+                    options: field.options,
+                    error: String::from(error),
+                    options_error: String::from(options_error),
+                },
+            )
+                .into_response())
+        }
+    }
+}
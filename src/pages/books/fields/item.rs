@@ -0,0 +1,29 @@
+use axum::{extract::State, response::IntoResponse};
+use axum_extra::routing::TypedPath;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, FieldId},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/fields/:id")]
+pub struct Path {
+    pub book: BookId,
+    pub id: FieldId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &FieldId) -> Self {
+        Self { book, id }
+    }
+}
+
+pub async fn delete(
+    Path { book, id }: Path,
+    State(fields): State<model::CustomFields>,
+) -> Result<impl IntoResponse> {
+    fields.delete_by_id(book, id).await?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::Flash;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, NameFormat},
+    pages::contacts,
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/name-format")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Setting {
+    name_format: NameFormat,
+}
+
+/// Changes how the names in the address book are shown and sorted.
+pub async fn post(
+    Path { book }: Path,
+    flash: Flash,
+    State(books): State<model::AddressBooks>,
+    Form(Setting { name_format }): Form<Setting>,
+) -> Result<Response> {
+    if !books.set_name_format(book, name_format).await? {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok((
+        flash.success("Name format saved"),
+        Redirect::to(&contacts::Path { book }.to_string()),
+    )
+        .into_response())
+}
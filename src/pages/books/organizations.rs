@@ -0,0 +1,138 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::{Flash, IncomingFlashes};
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId},
+    pages::contacts::{self, shared},
+    Result,
+};
+
+pub mod item;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/organizations")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "organizations.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub organizations: Vec<model::Organization>,
+    pub name: String,
+    pub error: String,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+    State(organizations): State<model::Organizations>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            book_name: address_book.name,
+            organizations: organizations.get_all(book).await?,
+            name: String::new(),
+            error: String::new(),
+        },
+    )
+        .into_response())
+}
+
+pub async fn post(
+    Path { book }: Path,
+    State(books): State<model::AddressBooks>,
+    State(organizations): State<model::Organizations>,
+    flash: Flash,
+    Form(organization): Form<model::OrganizationCandidate>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    match organizations.create(book, &organization).await {
+        Ok(id) => Ok((
+            flash.success("Organization created"),
+            Redirect::to(&item::Path { book, id }.to_string()),
+        )
+            .into_response()),
+        Err(model::Error::DuplicateOrganizationName) => Ok((
+            flash.error("Organization could not be created"),
+            Tmpl {
+                layout: shared::Layout { flashes: None },
+                book,
+                book_name: address_book.name,
+                organizations: organizations.get_all(book).await?,
+                name: organization.name,
+                error: String::from("Organization already exists"),
+            },
+        )
+            .into_response()),
+        Err(err) => Err(err)?,
+    }
+}
+
+pub mod names {
+    use axum::extract::{Query, State};
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId},
+        pages::contacts::tags,
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/organizations/names")]
+    pub struct Path {
+        pub book: BookId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId) -> Self {
+            Self { book }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        organization: String,
+    }
+
+    pub async fn get(
+        Path { book }: Path,
+        Query(Params { organization }): Query<Params>,
+        State(organizations): State<model::Organizations>,
+    ) -> Result<tags::Options> {
+        Ok(tags::Options {
+            options: organizations
+                .get_names_by_prefix(book, organization.trim())
+                .await?,
+        })
+    }
+}
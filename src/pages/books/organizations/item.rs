@@ -0,0 +1,118 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::{Flash, IncomingFlashes};
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, OrganizationId},
+    pages::contacts::{self, shared},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/organizations/:id")]
+pub struct Path {
+    pub book: BookId,
+    pub id: OrganizationId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &OrganizationId) -> Self {
+        Self { book, id }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "view-organization.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub organization: model::Organization,
+    pub members: Vec<shared::Contact>,
+    pub error: String,
+}
+
+impl Tmpl {
+    pub fn notes_html(&self) -> String {
+        shared::markdown_html(&self.organization.notes)
+    }
+}
+
+pub async fn get(
+    Path { book, id }: Path,
+    flashes: IncomingFlashes,
+    State(organizations): State<model::Organizations>,
+    State(contacts): State<model::Contacts>,
+) -> Result<Response> {
+    let Some(organization) = organizations.get_by_id(book, id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let members = contacts.get_by_organization(book, id).await?;
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            organization,
+            members: members.into_iter().map(shared::Contact::from).collect(),
+            error: String::new(),
+        },
+    )
+        .into_response())
+}
+
+pub async fn put(
+    Path { book, id }: Path,
+    State(organizations): State<model::Organizations>,
+    State(contacts): State<model::Contacts>,
+    flash: Flash,
+    Form(candidate): Form<model::OrganizationCandidate>,
+) -> Result<Response> {
+    match organizations.update_by_id(book, id, &candidate).await {
+        Ok(()) => Ok((
+            flash.success("Organization updated"),
+            Redirect::to(&Path { book, id }.to_string()),
+        )
+            .into_response()),
+        Err(model::Error::DuplicateOrganizationName) => {
+            let members = contacts.get_by_organization(book, id).await?;
+            Ok((
+                flash.error("Organization could not be saved"),
+                Tmpl {
+                    layout: shared::Layout { flashes: None },
+                    book,
+                    organization: model::Organization {
+                        id,
+                        name: candidate.name,
+                        website: candidate.website,
+                        notes: candidate.notes,
+                    },
+                    members: members.into_iter().map(shared::Contact::from).collect(),
+                    error: String::from("Organization already exists"),
+                },
+            )
+                .into_response())
+        }
+        Err(err) => Err(err)?,
+    }
+}
+
+pub async fn delete(
+    Path { book, id }: Path,
+    State(organizations): State<model::Organizations>,
+    flash: Flash,
+) -> Result<Response> {
+    organizations.delete_by_id(book, id).await?;
+    Ok((
+        flash.success("Organization deleted"),
+        Redirect::to(&super::Path { book }.to_string()),
+    )
+        .into_response())
+}
@@ -1,173 +1,433 @@
 use crate::{
-    model::{self, ArchiverStatus},
+    model::{self, ArchiverStatus, BookId, ContactOrder, ContactSort, NameFormat, SortDirection},
     Result,
 };
 use anyhow::Context;
 use askama::Template;
 use axum::{
     extract::{Query, RawForm, State},
+    http::StatusCode,
     response::{IntoResponse, Redirect, Response},
-    Form,
 };
-use axum_extra::routing::{TypedPath, WithQueryParams};
+use axum_extra::{
+    extract::Form,
+    routing::{TypedPath, WithQueryParams},
+};
 use axum_flash::{Flash, IncomingFlashes};
 use axum_htmx::HxTrigger;
 use serde::{Deserialize, Serialize};
 
 pub mod archive;
 pub mod count;
+pub mod duplicates;
 pub mod item;
+pub mod merge;
 pub mod new;
 pub mod shared;
+pub mod tags;
+pub mod trash;
+pub mod upcoming;
 
-#[derive(TypedPath)]
-#[typed_path("/contacts")]
-pub struct Path;
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts")]
+pub struct Path {
+    pub book: BookId,
+}
 
 #[derive(Template)]
 #[template(path = "contacts.html")]
 pub struct Page {
     pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub name_format: NameFormat,
     pub search_term: Option<String>,
+    pub tag: Option<String>,
+    pub favourites: bool,
+    pub fuzzy: bool,
+    pub order: ContactOrder,
     pub archiver_status: ArchiverStatus,
     pub page: u64,
     pub contacts: Vec<shared::Contact>,
+    /// Similar names to search for instead when the search found nothing.
+    pub suggestions: Vec<String>,
+    /// Why the search couldn't be understood.
+    pub search_error: Option<String>,
+}
+
+impl Page {
+    /// The current search, but across all tags.
+    pub fn without_tag(&self) -> WithQueryParams<Path, Params> {
+        Path::new(&self.book).with_params(
+            &self.search_term,
+            &None,
+            &self.favourites,
+            &self.fuzzy,
+            &self.order,
+            None,
+        )
+    }
+
+    pub fn headers(&self) -> Headers<'_> {
+        Headers {
+            book: self.book,
+            search_term: &self.search_term,
+            tag: &self.tag,
+            favourites: self.favourites,
+            fuzzy: self.fuzzy,
+            order: self.order,
+        }
+    }
 }
 
+/// The table of contacts alone, to replace it when searching as you type.
 #[derive(Template)]
-#[template(path = "contacts.html", block = "rows")]
-pub struct Rows {
+#[template(path = "contacts.html", block = "table")]
+pub struct Table {
+    pub book: BookId,
     pub contacts: Vec<shared::Contact>,
     pub search_term: Option<String>,
+    pub tag: Option<String>,
+    pub favourites: bool,
+    pub fuzzy: bool,
+    pub order: ContactOrder,
     pub page: u64,
+    pub suggestions: Vec<String>,
+}
+
+impl Table {
+    pub fn headers(&self) -> Headers<'_> {
+        Headers {
+            book: self.book,
+            search_term: &self.search_term,
+            tag: &self.tag,
+            favourites: self.favourites,
+            fuzzy: self.fuzzy,
+            order: self.order,
+        }
+    }
+}
+
+/// The sort links in the header of the table, which keep the current search.
+pub struct Headers<'a> {
+    book: BookId,
+    search_term: &'a Option<String>,
+    tag: &'a Option<String>,
+    favourites: bool,
+    fuzzy: bool,
+    order: ContactOrder,
+}
+
+impl Headers<'_> {
+    /// The current search, ordered by `sort`, or in reverse if it already is.
+    pub fn sorted_by(&self, sort: ContactSort) -> WithQueryParams<Path, Params> {
+        let direction = if Some(sort) == self.shown_sort() {
+            self.order.direction.reversed()
+        } else {
+            sort.default_direction()
+        };
+        Path::new(&self.book).with_params(
+            self.search_term,
+            self.tag,
+            &self.favourites,
+            &self.fuzzy,
+            &ContactOrder {
+                sort: Some(sort),
+                direction,
+            },
+            None,
+        )
+    }
+
+    /// The arrow for the header of column `sort` if the contacts are ordered by it.
+    pub fn sort_arrow(&self, sort: ContactSort) -> &'static str {
+        if Some(sort) != self.shown_sort() {
+            return "";
+        }
+        match self.order.direction {
+            SortDirection::Asc => "\u{2191}",
+            SortDirection::Desc => "\u{2193}",
+        }
+    }
+
+    /// The column the contacts are ordered by, which is the name unless searching.
+    fn shown_sort(&self) -> Option<ContactSort> {
+        let searching = self
+            .search_term
+            .as_deref()
+            .is_some_and(|q| !q.trim().is_empty());
+        self.order
+            .sort
+            .or((!searching).then_some(ContactSort::Name))
+    }
 }
+
 #[derive(Template)]
 #[template(path = "contacts.html", block = "archive")]
 pub struct Archive {
+    book: BookId,
     archiver_status: ArchiverStatus,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct Params {
     q: Option<String>,
+    tag: Option<String>,
+    /// Only list the starred contacts.
+    favourites: Option<bool>,
+    /// Search for similar names instead of the exact words.
+    fuzzy: Option<bool>,
+    sort: Option<ContactSort>,
+    dir: Option<SortDirection>,
     page: Option<u64>,
 }
 
 impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+
     pub fn with_params(
         self,
         q: &Option<String>,
+        tag: &Option<String>,
+        &favourites: &bool,
+        &fuzzy: &bool,
+        &ContactOrder { sort, direction }: &ContactOrder,
         page: Option<&u64>,
     ) -> WithQueryParams<Self, Params> {
         self.with_query_params(Params {
             q: q.clone(),
+            tag: tag.clone(),
+            favourites: favourites.then_some(true),
+            fuzzy: fuzzy.then_some(true),
+            sort,
+            dir: (direction != sort.unwrap_or_default().default_direction()).then_some(direction),
             page: page.copied(),
         })
     }
+
+    pub fn with_tag(self, tag: &str) -> WithQueryParams<Self, Params> {
+        self.with_query_params(Params {
+            q: None,
+            tag: Some(tag.to_owned()),
+            favourites: None,
+            fuzzy: None,
+            sort: None,
+            dir: None,
+            page: None,
+        })
+    }
+
+    /// The search `q` for the exact words, or for similar names if `fuzzy`.
+    pub fn with_fuzzy(
+        self,
+        q: &Option<String>,
+        tag: &Option<String>,
+        &favourites: &bool,
+        fuzzy: bool,
+        order: &ContactOrder,
+    ) -> WithQueryParams<Self, Params> {
+        self.with_params(q, tag, &favourites, &fuzzy, order, None)
+    }
+
+    /// The search `q` alone, in the current `order`.
+    pub fn with_search(self, q: &str, order: &ContactOrder) -> WithQueryParams<Self, Params> {
+        self.with_params(&Some(q.to_owned()), &None, &false, &false, order, None)
+    }
 }
 
 pub async fn get(
-    _: Path,
+    Path { book }: Path,
     flashes: IncomingFlashes,
     HxTrigger(hx_trigger): HxTrigger,
+    State(books): State<model::AddressBooks>,
     State(contacts): State<model::Contacts>,
-    State(archiver): State<model::Archiver>,
+    State(archivers): State<model::Archivers>,
     Query(query): Query<Params>,
 ) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
     let page = query.page.unwrap_or(1);
-    let contacts = match query.q {
+    let favourites = query.favourites.unwrap_or(false);
+    let fuzzy = query.fuzzy.unwrap_or(false);
+    let sort = query.sort;
+    let direction = query
+        .dir
+        .unwrap_or(sort.unwrap_or_default().default_direction());
+    let order = ContactOrder { sort, direction };
+    let found = match query.q {
+        Some(ref q) if fuzzy => {
+            contacts
+                .get_fuzzy_page(book, q, query.tag.as_deref(), favourites, order, page)
+                .await
+        }
         Some(ref q) => {
-            let result = contacts.get_filtered_page(q, page).await?;
-            result
-                .into_iter()
-                .map(|res| shared::Contact {
-                    id: res.id,
-                    first: res.first,
-                    last: res.last,
-                    phone: res.phone,
-                    email: res.email,
-                    errors: shared::ContactFieldErrors::default(),
-                })
-                .collect()
+            contacts
+                .get_filtered_page(book, q, query.tag.as_deref(), favourites, order, page)
+                .await
         }
         None => {
-            let result = contacts.get_page(page).await?;
-            result
-                .into_iter()
-                .map(|res| shared::Contact {
-                    id: res.id,
-                    first: res.first,
-                    last: res.last,
-                    phone: res.phone,
-                    email: res.email,
-                    errors: shared::ContactFieldErrors::default(),
-                })
-                .collect()
+            contacts
+                .get_page(book, query.tag.as_deref(), favourites, order, page)
+                .await
+        }
+    };
+    let is_search = matches!(hx_trigger.as_deref(), Some("search" | "favourites"));
+    let (found, search_error) = match found {
+        Ok(found) => (found, None),
+        // Searching as you type shows the error under the search box, keeping the old results.
+        Err(model::Error::InvalidSearch(error)) if is_search => {
+            return Ok((
+                [("HX-Retarget", "#search-error"), ("HX-Reswap", "innerHTML")],
+                error,
+            )
+                .into_response());
+        }
+        Err(model::Error::InvalidSearch(error)) => (Vec::new(), Some(error)),
+        Err(err) => return Err(err.into()),
+    };
+    let suggestions = match query.q {
+        Some(ref q) if found.is_empty() && !fuzzy && page == 1 => {
+            contacts.get_name_suggestions(book, q).await?
         }
+        _ => Vec::new(),
     };
-    match hx_trigger {
-        Some(trigger) if trigger == "search" => Ok(Rows {
+    let contacts = found.into_iter().map(shared::Contact::from).collect();
+    match is_search {
+        true => Ok(Table {
+            book,
             contacts,
             page,
             search_term: query.q,
+            tag: query.tag,
+            favourites,
+            fuzzy,
+            order,
+            suggestions,
         }
         .into_response()),
-        _ => Ok((
-            flashes.clone(),
-            Page {
-                layout: shared::Layout {
-                    flashes: Some(flashes),
+        false => {
+            let Some(archiver) = archivers.get(book).await? else {
+                return Ok(StatusCode::NOT_FOUND.into_response());
+            };
+            Ok((
+                flashes.clone(),
+                Page {
+                    layout: shared::Layout {
+                        flashes: Some(flashes),
+                    },
+                    book,
+                    book_name: address_book.name,
+                    name_format: address_book.name_format,
+                    archiver_status: archiver.status().await?,
+                    contacts,
+                    page,
+                    search_term: query.q,
+                    tag: query.tag,
+                    favourites,
+                    fuzzy,
+                    order,
+                    suggestions,
+                    search_error,
                 },
-                archiver_status: archiver.status().await?,
-                contacts,
-                page,
-                search_term: query.q,
-            },
-        )
-            .into_response()),
+            )
+                .into_response())
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PostParams {
+    /// Create the contact even if it looks like a duplicate.
+    #[serde(default)]
+    ignore_duplicates: bool,
+}
+
+impl Path {
+    /// Where to create a contact despite possible duplicates.
+    pub fn ignoring_duplicates(&book: &BookId) -> WithQueryParams<Self, PostParams> {
+        Self { book }.with_query_params(PostParams {
+            ignore_duplicates: true,
+        })
     }
 }
 
 pub async fn post(
-    _: Path,
+    Path { book }: Path,
     State(db): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
     flash: Flash,
+    Query(params): Query<PostParams>,
     Form(contact): Form<model::ContactCandidate>,
 ) -> Result<Response> {
-    let result = db.create(&contact).await;
-    match result {
-        Ok(id) => Ok((
-            flash.success("Contact created"),
-            Redirect::to(&item::Path { id }.to_string()),
-        )
-            .into_response()),
-        Err(model::Error::DuplicateEmail) => Ok((
-            flash.error("Contact could not be saved"),
-            new::Tmpl {
-                layout: shared::Layout { flashes: None },
-                contact: shared::Contact {
-                    id: Default::default(),
-                    first: contact.first,
-                    last: contact.last,
-                    phone: contact.phone,
-                    email: contact.email,
-                    errors: shared::ContactFieldErrors {
-                        email: String::from("Email already exists"),
-                        ..Default::default()
-                    },
+    if !params.ignore_duplicates {
+        let duplicates = db.get_duplicates_of(book, &contact).await?;
+        if !duplicates.is_empty() {
+            return Ok((
+                flash.warning("This contact might already exist"),
+                new::Tmpl {
+                    layout: shared::Layout { flashes: None },
+                    contact: shared::Contact::from_candidate(
+                        book,
+                        Default::default(),
+                        contact,
+                        &fields.get_all(book).await?,
+                        Default::default(),
+                    ),
+                    duplicates,
                 },
-            },
-        )
-            .into_response()),
+            )
+                .into_response());
+        }
+    }
+    let result = db.create(book, &contact).await;
+    let errors = match result {
+        Ok(id) => {
+            return Ok((
+                flash.success("Contact created"),
+                Redirect::to(&item::Path { book, id }.to_string()),
+            )
+                .into_response())
+        }
+        Err(model::Error::DuplicateEmail) => shared::ContactFieldErrors {
+            email: String::from("Email already exists"),
+            ..Default::default()
+        },
+        Err(model::Error::InvalidContact(errors)) => shared::ContactFieldErrors::invalid(errors),
+        Err(model::Error::InvalidCustomFields(custom)) => shared::ContactFieldErrors {
+            custom,
+            ..Default::default()
+        },
+        Err(model::Error::InvalidDate(occasion)) => {
+            shared::ContactFieldErrors::invalid_date(occasion)
+        }
         Err(err) => {
             eprintln!("{:?}", err);
             Err(err)?
         }
-    }
+    };
+    Ok((
+        StatusCode::UNPROCESSABLE_ENTITY,
+        flash.error("Contact could not be saved"),
+        new::Tmpl {
+            layout: shared::Layout { flashes: None },
+            contact: shared::Contact::from_candidate(
+                book,
+                Default::default(),
+                contact,
+                &fields.get_all(book).await?,
+                errors,
+            ),
+            duplicates: Vec::new(),
+        },
+    )
+        .into_response())
 }
 
 pub async fn delete(
-    _: Path,
+    Path { book }: Path,
     flash: Flash,
     State(contacts): State<model::Contacts>,
     RawForm(form): RawForm,
@@ -181,12 +441,12 @@ pub async fn delete(
             .and_then(|bytes| std::str::from_utf8(bytes).context("value was not utf-8"))
             .and_then(|s| s.parse().context("value was not a number"))?;
         if name == b"selected_contact_ids" {
-            contacts.delete_by_id(value).await?;
+            contacts.delete_by_id(book, value).await?;
         }
     }
     Ok((
-        flash.success("Contact deleted"),
-        Redirect::to(&Path.to_string()),
+        flash.success("Contacts moved to the trash"),
+        Redirect::to(&Path { book }.to_string()),
     )
         .into_response())
 }
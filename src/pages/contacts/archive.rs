@@ -1,42 +1,112 @@
-use crate::{model, Result};
+use crate::{
+    model::{self, BookId},
+    Result,
+};
+use serde::Deserialize;
 use tracing::instrument;
 
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use axum_extra::routing::TypedPath;
 
 pub mod file {
+    use axum::{
+        extract::{Request, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
     use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+    use tower_service::Service;
+
+    use crate::{
+        model::{self, BookId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/archive/file")]
+    pub struct Path {
+        pub book: BookId,
+    }
 
-    #[derive(TypedPath)]
-    #[typed_path("/contacts/archive/file")]
-    pub struct Path;
+    impl Path {
+        pub fn new(&book: &BookId) -> Self {
+            Self { book }
+        }
+    }
+
+    pub async fn get(
+        Path { book }: Path,
+        State(archivers): State<model::Archivers>,
+        req: Request,
+    ) -> Result<Response> {
+        let Some(archiver) = archivers.get(book).await? else {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        };
+        Ok(tower_http::services::ServeFile::new(archiver.file())
+            .call(req)
+            .await?
+            .into_response())
+    }
 }
 
-#[derive(TypedPath)]
-#[typed_path("/contacts/archive")]
-pub struct Path;
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/archive")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
 
 #[instrument(skip_all)]
-pub async fn post(_: Path, State(archiver): State<model::Archiver>) -> Result<Response> {
+pub async fn post(
+    Path { book }: Path,
+    State(archivers): State<model::Archivers>,
+) -> Result<Response> {
+    let Some(archiver) = archivers.get(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
     archiver.run().await?;
     Ok(super::Archive {
+        book,
         archiver_status: archiver.status().await?,
     }
     .into_response())
 }
 
-pub async fn get(_: Path, State(archiver): State<model::Archiver>) -> Result<super::Archive> {
+pub async fn get(
+    Path { book }: Path,
+    State(archivers): State<model::Archivers>,
+) -> Result<Response> {
+    let Some(archiver) = archivers.get(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
     Ok(super::Archive {
+        book,
         archiver_status: archiver.status().await?,
-    })
+    }
+    .into_response())
 }
 
-pub async fn delete(_: Path, State(archiver): State<model::Archiver>) -> Result<super::Archive> {
+pub async fn delete(
+    Path { book }: Path,
+    State(archivers): State<model::Archivers>,
+) -> Result<Response> {
+    let Some(archiver) = archivers.get(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
     archiver.reset().await?;
     Ok(super::Archive {
+        book,
         archiver_status: archiver.status().await?,
-    })
+    }
+    .into_response())
 }
@@ -1,12 +1,24 @@
-use crate::{model, Result};
+use crate::{
+    model::{self, BookId},
+    Result,
+};
 use axum::extract::State;
 use axum_extra::routing::TypedPath;
+use serde::Deserialize;
 
-#[derive(TypedPath)]
-#[typed_path("/contacts/count")]
-pub struct Path;
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/count")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
 
-pub async fn get(_: Path, State(contacts): State<model::Contacts>) -> Result<String> {
-    let count = contacts.count().await?;
+pub async fn get(Path { book }: Path, State(contacts): State<model::Contacts>) -> Result<String> {
+    let count = contacts.count(book).await?;
     Ok(format!("({} total Contacts)", count))
 }
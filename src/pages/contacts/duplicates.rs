@@ -0,0 +1,59 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::IncomingFlashes;
+use serde::Deserialize;
+
+use super::{item, shared};
+use crate::{
+    model::{self, BookId},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/duplicates")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "duplicates.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub duplicates: Vec<model::Duplicate>,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+    State(contacts): State<model::Contacts>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            book_name: address_book.name,
+            duplicates: contacts.get_duplicates(book).await?,
+        },
+    )
+        .into_response())
+}
@@ -3,30 +3,37 @@ use axum::{
     extract::State,
     http::StatusCode,
     response::{IntoResponse, Redirect, Response},
-    Form,
 };
-use axum_extra::routing::TypedPath;
+use axum_extra::{extract::Form, routing::TypedPath};
 use axum_flash::{Flash, IncomingFlashes};
 use axum_htmx::HxTrigger;
+use chrono::NaiveDate;
 use serde::Deserialize;
 
 use crate::{
-    model::{self, ContactId},
+    model::{self, BookId, ContactId, InteractionKind, RelationKind},
     pages::{self, contacts::shared},
     Result,
 };
 
 pub mod edit;
 pub mod email;
+pub mod history;
+pub mod interactions;
+pub mod photo;
+pub mod relations;
+pub mod reminders;
+pub mod star;
 
 #[derive(TypedPath, Deserialize)]
-#[typed_path("/contacts/:id")]
+#[typed_path("/books/:book/contacts/:id")]
 pub struct Path {
+    pub book: BookId,
     pub id: ContactId,
 }
 impl Path {
-    pub fn new(&id: &ContactId) -> Self {
-        Self { id }
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
     }
 }
 
@@ -35,25 +42,26 @@ impl Path {
 pub struct Tmpl {
     pub layout: shared::Layout,
     pub contact: shared::Contact,
+    pub relations: Vec<model::Relation>,
+    pub interactions: Vec<model::Interaction>,
+    pub reminders: Vec<model::Reminder>,
+    pub today: NaiveDate,
 }
 
 pub async fn get(
-    Path { id }: Path,
+    Path { book, id }: Path,
     flashes: IncomingFlashes,
     State(db): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
+    State(relations): State<model::Relations>,
+    State(interactions): State<model::Interactions>,
+    State(reminders): State<model::Reminders>,
 ) -> Result<Response> {
-    let contact = db.get_by_id(id).await?;
+    let contact = db.get_by_id(book, id).await?;
     let Some(contact) = contact else {
         return Ok(StatusCode::NOT_FOUND.into_response());
     };
-    let contact = shared::Contact {
-        id,
-        first: contact.first,
-        last: contact.last,
-        phone: contact.phone,
-        email: contact.email,
-        errors: Default::default(),
-    };
+    let contact = shared::Contact::with_fields(contact, &fields.get_all(book).await?);
     Ok((
         flashes.clone(),
         Tmpl {
@@ -61,60 +69,116 @@ pub async fn get(
                 flashes: Some(flashes),
             },
             contact,
+            relations: relations.get_for_contact(book, id).await?,
+            interactions: interactions.get_for_contact(book, id).await?,
+            reminders: reminders.get_for_contact(book, id).await?,
+            today: chrono::Local::now().date_naive(),
         },
     )
         .into_response())
 }
 
 pub async fn put(
-    Path { id }: Path,
+    Path { book, id }: Path,
     State(db): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
+    State(revisions): State<model::Revisions>,
     flash: Flash,
     Form(contact): Form<model::ContactCandidate>,
 ) -> Result<Response> {
-    let result = db.update_by_id(id, &contact).await;
-    match result {
-        Ok(res) => Ok((
-            flash.success("Contact updated"),
-            Redirect::to(&pages::contacts::item::Path { id: res }.to_string()),
-        )
-            .into_response()),
-        Err(model::Error::DuplicateEmail) => Ok((
-            flash.error("Contact could not be saved"),
-            edit::Tmpl {
-                layout: shared::Layout { flashes: None },
-                contact: shared::Contact {
-                    id,
-                    first: contact.first,
-                    last: contact.last,
-                    phone: contact.phone,
-                    email: contact.email,
-                    errors: shared::ContactFieldErrors {
-                        email: String::from("Email already exists"),
-                        ..Default::default()
-                    },
+    let result = db.update_by_id(book, id, &contact).await;
+    let errors = match result {
+        Ok(res) => {
+            return Ok((
+                flash.success("Contact updated"),
+                Redirect::to(&pages::contacts::item::Path { book, id: res }.to_string()),
+            )
+                .into_response())
+        }
+        Err(model::Error::DuplicateEmail) => shared::ContactFieldErrors {
+            email: String::from("Email already exists"),
+            ..Default::default()
+        },
+        Err(model::Error::InvalidContact(errors)) => shared::ContactFieldErrors::invalid(errors),
+        Err(model::Error::InvalidCustomFields(custom)) => shared::ContactFieldErrors {
+            custom,
+            ..Default::default()
+        },
+        Err(model::Error::InvalidDate(occasion)) => {
+            shared::ContactFieldErrors::invalid_date(occasion)
+        }
+        Err(model::Error::EditConflict) => {
+            let Some(current) = db.get_by_id(book, id).await? else {
+                return Ok(StatusCode::NOT_FOUND.into_response());
+            };
+            let fields = fields.get_all(book).await?;
+            let base = match contact.version {
+                Some(version) => revisions.get_by_version(book, id, version).await?,
+                None => None,
+            };
+            let theirs = model::ContactCandidate::from(&current);
+            // Without the version the edit is based on, all differences are shown.
+            let conflicts = match base {
+                Some(base) => contact.conflicts(&base.snapshot, &theirs, &fields),
+                None => contact.changes(Some(&theirs), &fields),
+            };
+            let contact = model::ContactCandidate {
+                version: Some(current.version),
+                ..contact
+            };
+            return Ok((
+                StatusCode::CONFLICT,
+                flash.error(
+                    "Contact was changed by someone else, saving again overwrites their changes",
+                ),
+                edit::Tmpl {
+                    layout: shared::Layout { flashes: None },
+                    contact: shared::Contact::from_candidate(
+                        book,
+                        id,
+                        contact,
+                        &fields,
+                        Default::default(),
+                    ),
+                    conflicts,
                 },
-            },
-        )
-            .into_response()),
+            )
+                .into_response());
+        }
         Err(err) => {
             eprintln!("{}", err);
             Err(err)?
         }
-    }
+    };
+    Ok((
+        StatusCode::UNPROCESSABLE_ENTITY,
+        flash.error("Contact could not be saved"),
+        edit::Tmpl {
+            layout: shared::Layout { flashes: None },
+            contact: shared::Contact::from_candidate(
+                book,
+                id,
+                contact,
+                &fields.get_all(book).await?,
+                errors,
+            ),
+            conflicts: Vec::new(),
+        },
+    )
+        .into_response())
 }
 
 pub async fn delete(
-    Path { id }: Path,
+    Path { book, id }: Path,
     flash: Flash,
     HxTrigger(hx_trigger): HxTrigger,
     State(contacts): State<model::Contacts>,
 ) -> Result<Response> {
-    contacts.delete_by_id(id).await?;
+    contacts.delete_by_id(book, id).await?;
     match hx_trigger.as_deref() {
         Some("delete-btn") => Ok((
-            flash.success("Contact deleted"),
-            Redirect::to(&pages::contacts::Path.to_string()),
+            flash.success("Contact moved to the trash"),
+            Redirect::to(&pages::contacts::Path { book }.to_string()),
         )
             .into_response()),
         _ => Ok(().into_response()),
@@ -1,5 +1,5 @@
 use crate::{
-    model::{self, ContactId},
+    model::{self, BookId, ContactId},
     pages::contacts::shared::{Contact, Layout},
     Result,
 };
@@ -14,14 +14,18 @@ use axum_flash::IncomingFlashes;
 use serde::Deserialize;
 
 #[derive(Deserialize, TypedPath)]
-#[typed_path("/contacts/:id/edit")]
+#[typed_path("/books/:book/contacts/:id/edit")]
 pub struct Path {
+    pub book: BookId,
     pub id: ContactId,
 }
 
 impl Path {
-    pub fn new(id: &ContactId) -> Self {
-        Self { id: *id }
+    pub fn new(book: &BookId, id: &ContactId) -> Self {
+        Self {
+            book: *book,
+            id: *id,
+        }
     }
 }
 
@@ -30,27 +34,23 @@ impl Path {
 pub struct Tmpl {
     pub layout: Layout,
     pub contact: Contact,
+    /// How the contact was changed by someone else while it was being edited, next to our values.
+    pub conflicts: Vec<model::Change>,
 }
 
 pub async fn get(
-    Path { id }: Path,
+    Path { book, id }: Path,
     flashes: IncomingFlashes,
     State(contacts): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
 ) -> Result<Response> {
-    let contact = contacts.get_by_id(id).await?;
+    let contact = contacts.get_by_id(book, id).await?;
 
     let Some(contact) = contact else {
         return Ok(StatusCode::NOT_FOUND.into_response());
     };
 
-    let contact = super::super::shared::Contact {
-        id,
-        first: contact.first,
-        last: contact.last,
-        phone: contact.phone,
-        email: contact.email,
-        errors: Default::default(),
-    };
+    let contact = Contact::with_fields(contact, &fields.get_all(book).await?);
     Ok((
         flashes.clone(),
         Tmpl {
@@ -58,6 +58,7 @@ pub async fn get(
                 flashes: Some(flashes),
             },
             contact,
+            conflicts: Vec::new(),
         },
     )
         .into_response())
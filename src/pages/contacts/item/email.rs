@@ -6,19 +6,23 @@ use axum_extra::routing::TypedPath;
 use serde::Deserialize;
 
 use crate::{
-    model::{self, ContactId},
+    model::{self, BookId, ContactId},
     Result,
 };
 
 #[derive(TypedPath, Deserialize)]
-#[typed_path("/contacts/:id/email")]
+#[typed_path("/books/:book/contacts/:id/email")]
 pub struct Path {
+    pub book: BookId,
     pub id: ContactId,
 }
 
 impl Path {
-    pub fn new(id: &ContactId) -> Self {
-        Self { id: *id }
+    pub fn new(book: &BookId, id: &ContactId) -> Self {
+        Self {
+            book: *book,
+            id: *id,
+        }
     }
 }
 
@@ -28,12 +32,15 @@ pub struct Params {
 }
 
 pub async fn get(
-    Path { id }: Path,
+    Path { book, id }: Path,
     Query(Params { email }): Query<Params>,
     State(contacts): State<model::Contacts>,
 ) -> Result<impl IntoResponse> {
-    let result = contacts.get_by_email(&email).await?;
+    let result = contacts.get_by_email(book, &email).await?;
     match result {
+        Some(res) if res.id != id && res.deleted_at.is_some() => {
+            Ok("Email belongs to a contact in the trash")
+        }
         Some(res) if res.id != id => Ok("Email already exists"),
         _ => Ok(""),
     }
@@ -0,0 +1,137 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::IncomingFlashes;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId, RevisionAction},
+    pages::contacts::shared,
+    Result,
+};
+
+pub mod item {
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        response::{IntoResponse, Redirect, Response},
+    };
+    use axum_extra::routing::TypedPath;
+    use axum_flash::Flash;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId, RevisionId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/history/:revision")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+        pub revision: RevisionId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId, &revision: &RevisionId) -> Self {
+            Self { book, id, revision }
+        }
+    }
+
+    /// Reverts the contact to the revision, which adds a new revision on top.
+    pub async fn post(
+        Path { book, id, revision }: Path,
+        State(contacts): State<model::Contacts>,
+        State(revisions): State<model::Revisions>,
+        flash: Flash,
+    ) -> Result<Response> {
+        let Some(revision) = revisions.get_by_id(book, id, revision).await? else {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        };
+        let flash = match contacts.update_by_id(book, id, &revision.snapshot).await {
+            Ok(_) => flash.success("Contact reverted"),
+            Err(model::Error::DuplicateEmail) => {
+                flash.error("Revision uses an email that belongs to another contact now")
+            }
+            Err(model::Error::InvalidCustomFields(_) | model::Error::InvalidDate(_)) => {
+                flash.error("Revision does not fit the current custom fields")
+            }
+            Err(model::Error::InvalidContact(_)) => {
+                flash.error("Revision has values that are not allowed anymore")
+            }
+            Err(err) => Err(err)?,
+        };
+        Ok((
+            flash,
+            Redirect::to(&crate::pages::contacts::item::Path { book, id }.to_string()),
+        )
+            .into_response())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/history")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+/// A revision with what it changed compared to the one before.
+pub struct Entry {
+    pub revision: model::Revision,
+    pub changes: Vec<model::Change>,
+}
+
+#[derive(Template)]
+#[template(path = "history.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub contact: model::Contact,
+    pub entries: Vec<Entry>,
+}
+
+pub async fn get(
+    Path { book, id }: Path,
+    flashes: IncomingFlashes,
+    State(contacts): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
+    State(revisions): State<model::Revisions>,
+) -> Result<Response> {
+    let Some(contact) = contacts.get_by_id(book, id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let fields = fields.get_all(book).await?;
+    let revisions = revisions.get_for_contact(book, id).await?;
+    let changes: Vec<_> = revisions
+        .iter()
+        .enumerate()
+        .map(|(i, revision)| revision.changes(revisions.get(i + 1), &fields))
+        .collect();
+    let entries = revisions
+        .into_iter()
+        .zip(changes)
+        .map(|(revision, changes)| Entry { revision, changes })
+        .collect();
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            contact,
+            entries,
+        },
+    )
+        .into_response())
+}
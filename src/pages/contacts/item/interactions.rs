@@ -0,0 +1,97 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId},
+    Result,
+};
+
+pub mod item {
+    use axum::{extract::State, response::IntoResponse};
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId, InteractionId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/interactions/:interaction")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+        pub interaction: InteractionId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId, &interaction: &InteractionId) -> Self {
+            Self {
+                book,
+                id,
+                interaction,
+            }
+        }
+    }
+
+    pub async fn delete(
+        Path {
+            book,
+            id,
+            interaction,
+        }: Path,
+        State(interactions): State<model::Interactions>,
+    ) -> Result<impl IntoResponse> {
+        interactions.delete_by_id(book, id, interaction).await?;
+        Ok(())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/interactions")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+/// The interactions of a contact, latest first.
+#[derive(Template)]
+#[template(path = "interactions.html")]
+pub struct Timeline {
+    pub book: BookId,
+    pub id: ContactId,
+    pub interactions: Vec<model::Interaction>,
+}
+
+/// Logs an interaction and answers with the updated timeline.
+pub async fn post(
+    Path { book, id }: Path,
+    State(interactions): State<model::Interactions>,
+    Form(interaction): Form<model::InteractionCandidate>,
+) -> Result<Response> {
+    match interactions.create(book, id, &interaction).await {
+        Ok(_) => {}
+        Err(err @ model::Error::InvalidInteraction) => {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response())
+        }
+        Err(err) => Err(err)?,
+    }
+    Ok(Timeline {
+        book,
+        id,
+        interactions: interactions.get_for_contact(book, id).await?,
+    }
+    .into_response())
+}
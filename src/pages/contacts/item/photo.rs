@@ -0,0 +1,143 @@
+use axum::{
+    extract::{multipart::MultipartError, Multipart, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::Flash;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId},
+    Result,
+};
+
+pub mod file {
+    use axum::{
+        extract::{Request, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+    use tower_service::Service;
+
+    use crate::{
+        model::{self, BookId, ContactId, PhotoSize},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/photo/:size")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+        pub size: PhotoSize,
+    }
+
+    impl Path {
+        pub fn small(&book: &BookId, &id: &ContactId) -> Self {
+            Self {
+                book,
+                id,
+                size: PhotoSize::Small,
+            }
+        }
+
+        pub fn large(&book: &BookId, &id: &ContactId) -> Self {
+            Self {
+                book,
+                id,
+                size: PhotoSize::Large,
+            }
+        }
+    }
+
+    pub async fn get(
+        Path { book, id, size }: Path,
+        State(photos): State<model::Photos>,
+        req: Request,
+    ) -> Result<Response> {
+        let Some(file) = photos.file(book, id, size).await? else {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        };
+        Ok(tower_http::services::ServeFile::new(file)
+            .call(req)
+            .await?
+            .into_response())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/photo")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+pub async fn post(
+    Path { book, id }: Path,
+    State(photos): State<model::Photos>,
+    flash: Flash,
+    mut form: Multipart,
+) -> Result<Response> {
+    let bytes = match read_photo(&mut form).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let message = if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+                "The photo is too large"
+            } else {
+                "The photo could not be uploaded"
+            };
+            return Ok((
+                flash.error(message),
+                Redirect::to(&super::edit::Path { book, id }.to_string()),
+            )
+                .into_response());
+        }
+    };
+    match photos.store(book, id, bytes).await {
+        Ok(true) => Ok((
+            flash.success("Photo uploaded"),
+            Redirect::to(&super::Path { book, id }.to_string()),
+        )
+            .into_response()),
+        Ok(false) => Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(model::Error::InvalidImage) => Ok((
+            flash.error("The file is not a supported image"),
+            Redirect::to(&super::edit::Path { book, id }.to_string()),
+        )
+            .into_response()),
+        Err(err) => Err(err)?,
+    }
+}
+
+/// The bytes of the `photo` field of the upload form, empty if there is none.
+async fn read_photo(form: &mut Multipart) -> std::result::Result<Vec<u8>, MultipartError> {
+    let mut bytes = Vec::new();
+    while let Some(field) = form.next_field().await? {
+        if field.name() == Some("photo") {
+            bytes = field.bytes().await?.to_vec();
+        }
+    }
+    Ok(bytes)
+}
+
+pub async fn delete(
+    Path { book, id }: Path,
+    State(photos): State<model::Photos>,
+    flash: Flash,
+) -> Result<Response> {
+    photos.delete(book, id).await?;
+    Ok((
+        flash.success("Photo removed"),
+        Redirect::to(&super::Path { book, id }.to_string()),
+    )
+        .into_response())
+}
@@ -0,0 +1,125 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::Flash;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId},
+    Result,
+};
+
+pub mod candidates {
+    use askama::Template;
+    use axum::extract::{Query, State};
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/relations/candidates")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+            Self { book, id }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct Params {
+        q: String,
+    }
+
+    #[derive(Template)]
+    #[template(path = "contact-options.html")]
+    pub struct Options {
+        contacts: Vec<model::Contact>,
+    }
+
+    /// The contacts matching `q` that contact `id` could be related to.
+    pub async fn get(
+        Path { book, id }: Path,
+        Query(Params { q }): Query<Params>,
+        State(contacts): State<model::Contacts>,
+    ) -> Result<Options> {
+        let mut contacts = match contacts
+            .get_filtered_page(book, q.trim(), None, false, Default::default(), 1)
+            .await
+        {
+            Err(model::Error::InvalidSearch(_)) => Vec::new(),
+            contacts => contacts?,
+        };
+        contacts.retain(|contact| contact.id != id);
+        Ok(Options { contacts })
+    }
+}
+
+pub mod item {
+    use axum::{extract::State, response::IntoResponse};
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId, RelationId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/relations/:relation")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+        pub relation: RelationId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId, &relation: &RelationId) -> Self {
+            Self { book, id, relation }
+        }
+    }
+
+    pub async fn delete(
+        Path { book, id, relation }: Path,
+        State(relations): State<model::Relations>,
+    ) -> Result<impl IntoResponse> {
+        relations.delete_by_id(book, id, relation).await?;
+        Ok(())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/relations")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+pub async fn post(
+    Path { book, id }: Path,
+    State(relations): State<model::Relations>,
+    flash: Flash,
+    Form(relation): Form<model::RelationCandidate>,
+) -> Result<Response> {
+    let flash = match relations.create(book, id, &relation).await {
+        Ok(_) => flash.success("Relation added"),
+        Err(model::Error::InvalidRelation) => flash.error("Relation could not be added"),
+        Err(err) => Err(err)?,
+    };
+    Ok((flash, Redirect::to(&super::Path { book, id }.to_string())).into_response())
+}
@@ -0,0 +1,101 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId},
+    Result,
+};
+
+pub mod item {
+    use axum::{extract::State, response::IntoResponse};
+    use axum_extra::{extract::Form, routing::TypedPath};
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId, ReminderId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/:id/reminders/:reminder")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+        pub reminder: ReminderId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId, &reminder: &ReminderId) -> Self {
+            Self { book, id, reminder }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct Done {
+        #[serde(default)]
+        done: bool,
+    }
+
+    /// Marks the reminder as done, or as not done if `done` is missing.
+    pub async fn post(
+        Path { book, id, reminder }: Path,
+        State(reminders): State<model::Reminders>,
+        Form(Done { done }): Form<Done>,
+    ) -> Result<impl IntoResponse> {
+        reminders.set_done(book, id, reminder, done).await?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        Path { book, id, reminder }: Path,
+        State(reminders): State<model::Reminders>,
+    ) -> Result<impl IntoResponse> {
+        reminders.delete_by_id(book, id, reminder).await?;
+        Ok(())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/reminders")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+/// The reminders of a contact.
+#[derive(Template)]
+#[template(path = "contact-reminders.html")]
+pub struct List {
+    pub reminders: Vec<model::Reminder>,
+}
+
+/// Adds a reminder and answers with the updated list.
+pub async fn post(
+    Path { book, id }: Path,
+    State(reminders): State<model::Reminders>,
+    Form(reminder): Form<model::ReminderCandidate>,
+) -> Result<Response> {
+    match reminders.create(book, id, &reminder).await {
+        Ok(_) => {}
+        Err(err @ model::Error::InvalidReminder) => {
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response())
+        }
+        Err(err) => Err(err)?,
+    }
+    Ok(List {
+        reminders: reminders.get_for_contact(book, id).await?,
+    }
+    .into_response())
+}
@@ -0,0 +1,62 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId, ContactId},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/:id/star")]
+pub struct Path {
+    pub book: BookId,
+    pub id: ContactId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+        Self { book, id }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "star-button.html")]
+pub struct Button {
+    pub book: BookId,
+    pub id: ContactId,
+    pub starred: bool,
+}
+
+async fn set_starred(
+    contacts: model::Contacts,
+    book: BookId,
+    id: ContactId,
+    starred: bool,
+) -> Result<Response> {
+    if !contacts.set_starred(book, id, starred).await? {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(Button { book, id, starred }.into_response())
+}
+
+/// Adds the contact to the favourites.
+pub async fn post(
+    Path { book, id }: Path,
+    State(contacts): State<model::Contacts>,
+) -> Result<Response> {
+    set_starred(contacts, book, id, true).await
+}
+
+/// Removes the contact from the favourites.
+pub async fn delete(
+    Path { book, id }: Path,
+    State(contacts): State<model::Contacts>,
+) -> Result<Response> {
+    set_starred(contacts, book, id, false).await
+}
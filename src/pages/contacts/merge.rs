@@ -0,0 +1,352 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::{extract::Form, routing::TypedPath};
+use axum_flash::{Flash, IncomingFlashes};
+use serde::Deserialize;
+
+use super::{item, shared};
+use crate::{
+    model::{self, BookId, ContactCandidate, ContactId},
+    Result,
+};
+
+/// The fields that can be picked from one of the merged contacts, by form name and by the label
+/// they have in [`ContactCandidate::summary`].
+const FIELDS: [(&str, &str); 18] = [
+    ("first", "First Name"),
+    ("last", "Last Name"),
+    ("prefix", "Prefix"),
+    ("middle", "Middle Name"),
+    ("suffix", "Suffix"),
+    ("nickname", "Nickname"),
+    ("phonetic_first", "Phonetic First Name"),
+    ("phonetic_last", "Phonetic Last Name"),
+    ("phones", "Phones"),
+    ("emails", "Emails"),
+    ("addresses", "Addresses"),
+    ("tags", "Tags"),
+    ("organization", "Company"),
+    ("job_title", "Job Title"),
+    ("department", "Department"),
+    ("birthday", "Birthday"),
+    ("anniversary", "Anniversary"),
+    ("notes", "Notes"),
+];
+
+/// The fields whose values can be combined from all merged contacts instead.
+const COMBINABLE: [&str; 4] = ["phones", "emails", "addresses", "tags"];
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/merge")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Selection {
+    #[serde(default)]
+    selected_contact_ids: Vec<ContactId>,
+}
+
+/// A field with the value of each of the contacts to choose from.
+pub struct Row {
+    pub name: String,
+    pub label: String,
+    pub cells: Vec<Cell>,
+    /// Whether "combine all" is checked, if the field can be combined.
+    pub combined: Option<bool>,
+}
+
+pub struct Cell {
+    pub id: ContactId,
+    pub value: String,
+    pub checked: bool,
+}
+
+#[derive(Template)]
+#[template(path = "merge.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub contacts: Vec<model::Contact>,
+    pub rows: Vec<Row>,
+}
+
+/// Loads the contacts with `ids`, or nothing if any of them doesn't exist.
+async fn get_all(
+    contacts: &model::Contacts,
+    book: BookId,
+    ids: &[ContactId],
+) -> Result<Option<Vec<model::Contact>>> {
+    let mut loaded = Vec::new();
+    for &id in ids {
+        match contacts.get_by_id(book, id).await? {
+            Some(contact) => loaded.push(contact),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(loaded))
+}
+
+/// Lets the user pick which contact each field is taken from.
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    flash: Flash,
+    State(contacts): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
+    Form(Selection {
+        selected_contact_ids,
+    }): Form<Selection>,
+) -> Result<Response> {
+    if selected_contact_ids.len() < 2 {
+        return Ok((
+            flash.error("Select at least two contacts to merge"),
+            Redirect::to(&super::Path { book }.to_string()),
+        )
+            .into_response());
+    }
+    let Some(contacts) = get_all(&contacts, book, &selected_contact_ids).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let fields = fields.get_all(book).await?;
+    let candidates: Vec<ContactCandidate> = contacts.iter().map(ContactCandidate::from).collect();
+    let summaries: Vec<Vec<(String, String)>> = candidates
+        .iter()
+        .map(|candidate| candidate.summary(&fields))
+        .collect();
+    let row = |name: String, label: String, values: Vec<String>, combinable: bool| {
+        // Prefer the first contact that has a value at all.
+        let default = values.iter().position(|value| !value.is_empty());
+        Row {
+            cells: contacts
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(i, (contact, value))| Cell {
+                    id: contact.id,
+                    value,
+                    checked: !combinable && default.unwrap_or(0) == i,
+                })
+                .collect(),
+            name,
+            label,
+            combined: combinable.then_some(true),
+        }
+    };
+    let mut rows: Vec<Row> = FIELDS
+        .iter()
+        .map(|&(name, label)| {
+            let values = summaries
+                .iter()
+                .map(|summary| {
+                    summary
+                        .iter()
+                        .find(|(field, _)| field == label)
+                        .map(|(_, value)| value.clone())
+                        .unwrap_or_default()
+                })
+                .collect();
+            row(
+                name.to_owned(),
+                label.to_owned(),
+                values,
+                COMBINABLE.contains(&name),
+            )
+        })
+        .collect();
+    for field in &fields {
+        let id = field.id.to_string();
+        let values = candidates
+            .iter()
+            .map(|candidate| candidate.custom().remove(&id).unwrap_or_default())
+            .collect();
+        rows.push(row(
+            format!("custom:{id}"),
+            field.name.clone(),
+            values,
+            false,
+        ));
+    }
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            contacts,
+            rows,
+        },
+    )
+        .into_response())
+}
+
+/// Merges the contacts into the one to keep, with the fields taken from the picked contacts.
+pub async fn post(
+    Path { book }: Path,
+    flash: Flash,
+    State(contacts): State<model::Contacts>,
+    State(fields): State<model::CustomFields>,
+    State(photos): State<model::Photos>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Response> {
+    let ids: Vec<ContactId> = form
+        .iter()
+        .filter(|(name, _)| name == "contact")
+        .filter_map(|(_, id)| id.parse().ok())
+        .collect();
+    let picked = |name: &str| {
+        form.iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let Some(survivor) = picked("survivor").and_then(|id| id.parse::<ContactId>().ok()) else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+    let Some(loaded) = get_all(&contacts, book, &ids).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    // The contact to keep goes first, so that its values win when combining.
+    let mut candidates: Vec<(ContactId, ContactCandidate)> = loaded
+        .iter()
+        .map(|contact| (contact.id, ContactCandidate::from(contact)))
+        .collect();
+    let Some(position) = candidates.iter().position(|(id, _)| *id == survivor) else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+    candidates[..=position].rotate_right(1);
+    let from = |name: &str| -> Vec<&ContactCandidate> {
+        match picked(name) {
+            Some("all") => candidates.iter().map(|(_, candidate)| candidate).collect(),
+            picked => vec![
+                &candidates
+                    .iter()
+                    .find(|(id, _)| Some(id.to_string().as_str()) == picked)
+                    .unwrap_or(&candidates[0])
+                    .1,
+            ],
+        }
+    };
+    let single = |name: &str| from(name)[0];
+
+    let mut merged = ContactCandidate {
+        first: single("first").first.clone(),
+        last: single("last").last.clone(),
+        prefix: single("prefix").prefix.clone(),
+        middle: single("middle").middle.clone(),
+        suffix: single("suffix").suffix.clone(),
+        nickname: single("nickname").nickname.clone(),
+        phonetic_first: single("phonetic_first").phonetic_first.clone(),
+        phonetic_last: single("phonetic_last").phonetic_last.clone(),
+        notes: single("notes").notes.clone(),
+        organization: single("organization").organization.clone(),
+        job_title: single("job_title").job_title.clone(),
+        department: single("department").department.clone(),
+        birthday: single("birthday").birthday.clone(),
+        anniversary: single("anniversary").anniversary.clone(),
+        ..Default::default()
+    };
+    for candidate in from("phones") {
+        for phone in candidate.phones() {
+            let digits = |number: &str| {
+                number
+                    .chars()
+                    .filter(char::is_ascii_digit)
+                    .collect::<String>()
+            };
+            if !merged
+                .phone
+                .iter()
+                .any(|other| digits(other) == digits(&phone.number))
+            {
+                merged.phone_label.push(phone.label);
+                merged.phone.push(phone.number);
+            }
+        }
+    }
+    for candidate in from("emails") {
+        for email in candidate.emails() {
+            if !merged
+                .email
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(&email.address))
+            {
+                merged.email_label.push(email.label);
+                merged.email.push(email.address);
+            }
+        }
+    }
+    let mut addresses: Vec<model::Address> = Vec::new();
+    for candidate in from("addresses") {
+        for address in candidate.addresses() {
+            if !addresses
+                .iter()
+                .any(|other| other.one_line() == address.one_line())
+            {
+                addresses.push(address);
+            }
+        }
+    }
+    for address in addresses {
+        merged.address_label.push(address.label);
+        merged.address_street.push(address.street);
+        merged.address_locality.push(address.locality);
+        merged.address_region.push(address.region);
+        merged.address_postal_code.push(address.postal_code);
+        merged.address_country.push(address.country);
+    }
+    for candidate in from("tags") {
+        for tag in candidate.tags() {
+            if !merged.tag.contains(&tag) {
+                merged.tag.push(tag);
+            }
+        }
+    }
+    for field in fields.get_all(book).await? {
+        let id = field.id.to_string();
+        if let Some(value) = single(&format!("custom:{id}")).custom().remove(&id) {
+            merged.custom_field.push(id);
+            merged.custom_value.push(value);
+        }
+    }
+
+    let others: Vec<ContactId> = candidates[1..].iter().map(|(id, _)| *id).collect();
+    let flash = match contacts.merge(book, survivor, &others, &merged).await {
+        Ok(_) => {
+            for &other in &others {
+                photos.remove_files(other).await?;
+            }
+            flash.success("Contacts merged")
+        }
+        Err(
+            model::Error::InvalidMerge
+            | model::Error::DuplicateEmail
+            | model::Error::InvalidContact(_)
+            | model::Error::InvalidCustomFields(_)
+            | model::Error::InvalidDate(_),
+        ) => {
+            return Ok((
+                flash.error("Contacts could not be merged"),
+                Redirect::to(&super::Path { book }.to_string()),
+            )
+                .into_response())
+        }
+        Err(err) => Err(err)?,
+    };
+    Ok((
+        flash,
+        Redirect::to(&item::Path { book, id: survivor }.to_string()),
+    )
+        .into_response())
+}
@@ -1,27 +1,66 @@
 use askama::Template;
-use axum::response::IntoResponse;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+};
 use axum_extra::routing::TypedPath;
 use axum_flash::IncomingFlashes;
 use serde::Deserialize;
 
 use super::shared;
+use crate::{
+    model::{self, BookId},
+    Result,
+};
 
 #[derive(Template)]
 #[template(path = "new-contact.html")]
 pub struct Tmpl {
     pub layout: shared::Layout,
     pub contact: shared::Contact,
+    /// Existing contacts the submitted one is likely a duplicate of.
+    pub duplicates: Vec<model::Contact>,
 }
 
 #[derive(TypedPath, Deserialize)]
-#[typed_path("/contacts/new")]
-pub struct Path;
+#[typed_path("/books/:book/contacts/new")]
+pub struct Path {
+    pub book: BookId,
+}
 
-pub async fn get(_: Path, flashes: IncomingFlashes) -> impl IntoResponse {
-    Tmpl {
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(fields): State<model::CustomFields>,
+) -> Result<Response> {
+    let custom = fields
+        .get_all(book)
+        .await?
+        .into_iter()
+        .map(|field| shared::CustomEntry {
+            field,
+            value: String::new(),
+        })
+        .collect();
+    Ok(Tmpl {
         layout: shared::Layout {
             flashes: Some(flashes),
         },
-        contact: shared::Contact::default(),
+        contact: shared::Contact {
+            book,
+            phones: vec![Default::default()],
+            emails: vec![Default::default()],
+            addresses: vec![Default::default()],
+            custom,
+            ..Default::default()
+        },
+        duplicates: Vec::new(),
     }
+    .into_response())
 }
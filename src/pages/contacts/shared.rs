@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
+
 use askama::Template;
+use chrono::NaiveDate;
 
-use crate::model::ContactId;
+use crate::model::{self, BookId, ContactId, FieldKind};
 
 #[derive(Template)]
 #[template(path = "contact-fields.html")]
@@ -32,16 +35,268 @@ impl Layout {
 #[derive(Default, Clone)]
 pub struct Contact {
     pub id: ContactId,
+    pub book: BookId,
     pub first: String,
     pub last: String,
-    pub phone: String,
-    pub email: String,
+    pub prefix: String,
+    pub middle: String,
+    pub suffix: String,
+    pub nickname: String,
+    pub phonetic_first: String,
+    pub phonetic_last: String,
+    /// The full name in the format of the address book.
+    pub name: String,
+    pub phones: Vec<model::Phone>,
+    pub emails: Vec<model::Email>,
+    pub addresses: Vec<model::Address>,
+    pub tags: Vec<String>,
+    pub notes: String,
+    pub custom: Vec<CustomEntry>,
+    pub has_photo: bool,
+    pub birthday: String,
+    pub anniversary: String,
+    pub organization_id: Option<model::OrganizationId>,
+    pub organization: String,
+    pub job_title: String,
+    pub department: String,
+    pub version: i32,
+    pub starred: bool,
+    pub last_interaction: Option<NaiveDate>,
     pub errors: ContactFieldErrors,
 }
+
+/// A custom field of the address book together with the contact's value for it.
+#[derive(Clone)]
+pub struct CustomEntry {
+    pub field: model::CustomField,
+    pub value: String,
+}
+
+impl CustomEntry {
+    /// The value as shown on the contact page.
+    pub fn display(&self) -> &str {
+        match (self.field.kind, self.value.as_str()) {
+            (FieldKind::Boolean, "true") => "Yes",
+            (FieldKind::Boolean, "false") => "No",
+            (_, value) => value,
+        }
+    }
+}
+
+impl Contact {
+    /// A contact as submitted through the form, to be shown again alongside `errors`.
+    pub fn from_candidate(
+        book: BookId,
+        id: ContactId,
+        candidate: model::ContactCandidate,
+        fields: &[model::CustomField],
+        errors: ContactFieldErrors,
+    ) -> Self {
+        let values = candidate.custom();
+        Self {
+            id,
+            book,
+            phones: candidate.phones(),
+            emails: candidate.emails(),
+            addresses: candidate.addresses(),
+            tags: candidate.tags(),
+            notes: candidate.notes,
+            name: String::new(),
+            first: candidate.first,
+            last: candidate.last,
+            prefix: candidate.prefix,
+            middle: candidate.middle,
+            suffix: candidate.suffix,
+            nickname: candidate.nickname,
+            phonetic_first: candidate.phonetic_first,
+            phonetic_last: candidate.phonetic_last,
+            custom: fields
+                .iter()
+                .map(|field| CustomEntry {
+                    field: field.clone(),
+                    value: values
+                        .get(&field.id.to_string())
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+                .collect(),
+            has_photo: false,
+            birthday: candidate.birthday,
+            anniversary: candidate.anniversary,
+            organization_id: None,
+            organization: candidate.organization,
+            job_title: candidate.job_title,
+            department: candidate.department,
+            version: candidate.version.unwrap_or_default(),
+            starred: false,
+            last_interaction: None,
+            errors,
+        }
+    }
+
+    /// The contact along with its values for all custom `fields` of the address book.
+    pub fn with_fields(contact: model::Contact, fields: &[model::CustomField]) -> Self {
+        let custom = fields
+            .iter()
+            .map(|field| CustomEntry {
+                field: field.clone(),
+                value: contact
+                    .custom
+                    .get(&field.id.to_string())
+                    .map(model::format_value)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Self {
+            custom,
+            ..Self::from(contact)
+        }
+    }
+
+    /// The birthday in words, or nothing if there is none.
+    pub fn birthday_long(&self) -> String {
+        long_date(&self.birthday)
+    }
+
+    /// The anniversary in words, or nothing if there is none.
+    pub fn anniversary_long(&self) -> String {
+        long_date(&self.anniversary)
+    }
+
+    pub fn custom_error(&self, entry: &CustomEntry) -> &str {
+        self.errors
+            .custom
+            .get(&entry.field.id.to_string())
+            .map_or("", String::as_str)
+    }
+
+    /// The notes rendered from Markdown, with anything unsafe stripped out.
+    pub fn notes_html(&self) -> String {
+        markdown_html(&self.notes)
+    }
+
+    /// Job title and department, e.g. "Engineer, Research".
+    pub fn position(&self) -> String {
+        [self.job_title.as_str(), &self.department]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn primary_phone(&self) -> &str {
+        self.phones
+            .iter()
+            .find(|phone| phone.primary)
+            .map_or("", |phone| &phone.number)
+    }
+
+    pub fn primary_email(&self) -> &str {
+        self.emails
+            .iter()
+            .find(|email| email.primary)
+            .map_or("", |email| &email.address)
+    }
+}
+
+impl From<model::Contact> for Contact {
+    fn from(contact: model::Contact) -> Self {
+        Self {
+            name: contact.name(),
+            id: contact.id,
+            book: contact.book,
+            first: contact.first,
+            last: contact.last,
+            prefix: contact.prefix,
+            middle: contact.middle,
+            suffix: contact.suffix,
+            nickname: contact.nickname,
+            phonetic_first: contact.phonetic_first,
+            phonetic_last: contact.phonetic_last,
+            phones: contact.phones,
+            emails: contact.emails,
+            addresses: contact.addresses,
+            tags: contact.tags,
+            notes: contact.notes,
+            custom: Vec::new(),
+            has_photo: contact.has_photo,
+            birthday: contact
+                .birthday
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            anniversary: contact
+                .anniversary
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            organization_id: contact.organization.as_ref().map(|org| org.id),
+            organization: contact.organization.map(|org| org.name).unwrap_or_default(),
+            job_title: contact.job_title,
+            department: contact.department,
+            version: contact.version,
+            starred: contact.starred,
+            last_interaction: contact.last_interaction,
+            errors: ContactFieldErrors::default(),
+        }
+    }
+}
 #[derive(Clone, Default)]
 pub struct ContactFieldErrors {
     pub first: String,
     pub last: String,
     pub phone: String,
     pub email: String,
+    pub birthday: String,
+    pub anniversary: String,
+    pub custom: BTreeMap<String, String>,
+    /// The errors of the remaining fields, keyed by their form name.
+    pub other: BTreeMap<String, String>,
+}
+
+impl ContactFieldErrors {
+    /// Sorts the errors of [`model::Error::InvalidContact`] into the slots of the form.
+    pub fn invalid(mut errors: BTreeMap<String, String>) -> Self {
+        let mut take = |field: &str| errors.remove(field).unwrap_or_default();
+        Self {
+            first: take("first"),
+            last: take("last"),
+            phone: take("phone"),
+            email: take("email"),
+            birthday: take("birthday"),
+            anniversary: take("anniversary"),
+            custom: BTreeMap::new(),
+            other: errors,
+        }
+    }
+
+    /// The error of one of the remaining fields, or nothing.
+    pub fn other(&self, field: &str) -> &str {
+        self.other.get(field).map_or("", String::as_str)
+    }
+
+    pub fn invalid_date(occasion: model::Occasion) -> Self {
+        let error = String::from("Must be a date like 1990-12-31, or --12-31 without the year");
+        match occasion {
+            model::Occasion::Birthday => Self {
+                birthday: error,
+                ..Default::default()
+            },
+            model::Occasion::Anniversary => Self {
+                anniversary: error,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Renders Markdown `text` to HTML, with anything unsafe stripped out.
+pub fn markdown_html(text: &str) -> String {
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new(text));
+    ammonia::clean(&html)
+}
+
+fn long_date(date: &str) -> String {
+    date.parse::<model::PartialDate>()
+        .map(|date| date.long())
+        .unwrap_or_default()
 }
@@ -0,0 +1,43 @@
+use askama::Template;
+use axum::extract::{Query, State};
+use axum_extra::routing::TypedPath;
+use serde::Deserialize;
+
+use crate::{
+    model::{self, BookId},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/tags")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Params {
+    tag: String,
+}
+
+/// Suggestions for an `<input>` with a `<datalist>`.
+#[derive(Template)]
+#[template(path = "options.html")]
+pub struct Options {
+    pub options: Vec<String>,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    Query(Params { tag }): Query<Params>,
+    State(contacts): State<model::Contacts>,
+) -> Result<Options> {
+    Ok(Options {
+        options: contacts.get_tags_by_prefix(book, tag.trim()).await?,
+    })
+}
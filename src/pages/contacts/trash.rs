@@ -0,0 +1,112 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::IncomingFlashes;
+use serde::Deserialize;
+
+use super::shared;
+use crate::{
+    model::{self, BookId},
+    Result,
+};
+
+pub mod item {
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    use crate::{
+        model::{self, BookId, ContactId},
+        Result,
+    };
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/books/:book/contacts/trash/:id")]
+    pub struct Path {
+        pub book: BookId,
+        pub id: ContactId,
+    }
+
+    impl Path {
+        pub fn new(&book: &BookId, &id: &ContactId) -> Self {
+            Self { book, id }
+        }
+    }
+
+    /// Restores the contact from the trash.
+    pub async fn post(
+        Path { book, id }: Path,
+        State(contacts): State<model::Contacts>,
+    ) -> Result<impl IntoResponse> {
+        contacts.restore_by_id(book, id).await?;
+        Ok(())
+    }
+
+    /// Deletes the contact for good.
+    pub async fn delete(
+        Path { book, id }: Path,
+        State(contacts): State<model::Contacts>,
+        State(photos): State<model::Photos>,
+    ) -> Result<Response> {
+        if !contacts.purge_by_id(book, id).await? {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+        photos.remove_files(id).await?;
+        Ok(().into_response())
+    }
+}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/trash")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "trash.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub retention_days: i64,
+    pub contacts: Vec<model::Contact>,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+    State(contacts): State<model::Contacts>,
+    State(purger): State<model::Purger>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            book_name: address_book.name,
+            retention_days: purger.retention_days(),
+            contacts: contacts.get_trash(book).await?,
+        },
+    )
+        .into_response())
+}
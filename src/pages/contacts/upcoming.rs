@@ -0,0 +1,69 @@
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::IncomingFlashes;
+use serde::Deserialize;
+
+use super::{item, shared};
+use crate::{
+    model::{self, BookId, Occasion},
+    Result,
+};
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/books/:book/contacts/upcoming")]
+pub struct Path {
+    pub book: BookId,
+}
+
+impl Path {
+    pub fn new(&book: &BookId) -> Self {
+        Self { book }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Params {
+    days: Option<i64>,
+}
+
+#[derive(Template)]
+#[template(path = "upcoming.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub book: BookId,
+    pub book_name: String,
+    pub days: i64,
+    pub upcoming: Vec<model::Upcoming>,
+}
+
+pub async fn get(
+    Path { book }: Path,
+    flashes: IncomingFlashes,
+    State(books): State<model::AddressBooks>,
+    State(contacts): State<model::Contacts>,
+    Query(params): Query<Params>,
+) -> Result<Response> {
+    let Some(address_book) = books.get_by_id(book).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let days = params.days.unwrap_or(30).clamp(0, 366);
+    let today = chrono::Local::now().date_naive();
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            book,
+            book_name: address_book.name,
+            days,
+            upcoming: contacts.get_upcoming(book, today, days).await?,
+        },
+    )
+        .into_response())
+}
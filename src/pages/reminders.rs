@@ -0,0 +1,76 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use axum_extra::routing::TypedPath;
+use axum_flash::IncomingFlashes;
+
+use crate::{model, pages::contacts::shared, Result};
+
+pub mod overdue {
+    use askama::Template;
+    use axum::extract::State;
+    use axum_extra::routing::TypedPath;
+
+    use crate::{model, Result};
+
+    #[derive(TypedPath)]
+    #[typed_path("/reminders/overdue")]
+    pub struct Path;
+
+    /// A badge with the number of overdue reminders, or nothing if there are none.
+    #[derive(Template)]
+    #[template(path = "overdue-badge.html")]
+    pub struct Badge {
+        pub count: i64,
+    }
+
+    pub async fn get(_: Path, State(reminders): State<model::Reminders>) -> Result<Badge> {
+        let count = reminders
+            .count_overdue(chrono::Local::now().date_naive())
+            .await?;
+        Ok(Badge { count })
+    }
+}
+
+#[derive(TypedPath)]
+#[typed_path("/reminders")]
+pub struct Path;
+
+#[derive(Template)]
+#[template(path = "reminders.html")]
+pub struct Tmpl {
+    pub layout: shared::Layout,
+    pub overdue: Vec<model::Reminder>,
+    pub due_today: Vec<model::Reminder>,
+    pub upcoming: Vec<model::Reminder>,
+}
+
+pub async fn get(
+    _: Path,
+    flashes: IncomingFlashes,
+    State(reminders): State<model::Reminders>,
+) -> Result<Response> {
+    let today = chrono::Local::now().date_naive();
+    let (mut overdue, mut due_today, mut upcoming) = (Vec::new(), Vec::new(), Vec::new());
+    for reminder in reminders.get_open().await? {
+        match reminder.due.cmp(&today) {
+            std::cmp::Ordering::Less => overdue.push(reminder),
+            std::cmp::Ordering::Equal => due_today.push(reminder),
+            std::cmp::Ordering::Greater => upcoming.push(reminder),
+        }
+    }
+    Ok((
+        flashes.clone(),
+        Tmpl {
+            layout: shared::Layout {
+                flashes: Some(flashes),
+            },
+            overdue,
+            due_today,
+            upcoming,
+        },
+    )
+        .into_response())
+}